@@ -15,7 +15,7 @@ fn decode(c: &mut criterion::Criterion) {
                 let data = std::io::Cursor::new(data.as_slice());
                 let decoder =
                     png_pong::Decoder::new(data).expect("Not PNG").into_steps();
-                let png_pong::Step { raster, delay: _ } = decoder
+                let png_pong::Step { raster, .. } = decoder
                     .last()
                     .expect("No frames in PNG")
                     .expect("PNG parsing error");
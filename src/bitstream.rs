@@ -50,6 +50,8 @@ pub(super) struct BitstreamReader<R: Read> {
     byte: Option<u8>,
 }
 
+// Only ever constructed from in-memory cursors.
+#[allow(clippy::unbuffered_bytes)]
 impl<R: Read> BitstreamReader<R> {
     /// Create a new `BitstreamReader` from a type that implements `Read`.
     #[inline(always)]
@@ -0,0 +1,345 @@
+//! APNG frame compositing
+
+use pix::{rgb::Rgb, Raster, Region};
+
+use crate::{
+    chunk::{BlendOp, DisposeOp, FrameControl},
+    PngRaster,
+};
+
+/// Full-size RGBA output buffer that APNG frames are drawn onto.
+#[derive(Clone, Debug)]
+pub(crate) struct Canvas {
+    /// Width of the output buffer
+    width: u32,
+    /// Height of the output buffer
+    height: u32,
+    /// Bytes per channel (1 or 2)
+    bytes: usize,
+    /// RGBA pixels, 16-bit channels are stored big endian
+    data: Vec<u8>,
+    /// Disposal of the last drawn frame, with the saved region contents for
+    /// `DisposeOp::Previous`
+    dispose: Option<(FrameControl, Vec<u8>)>,
+    /// Whether or not a frame has been drawn yet
+    drawn: bool,
+}
+
+impl Canvas {
+    /// Create a new fully transparent black canvas.
+    pub(crate) fn new(width: u32, height: u32, wide: bool) -> Self {
+        let bytes = if wide { 2 } else { 1 };
+        Self {
+            width,
+            height,
+            bytes,
+            data: vec![0; width as usize * height as usize * 4 * bytes],
+            dispose: None,
+            drawn: false,
+        }
+    }
+
+    /// Dispose of the previous frame, then draw the next frame onto the
+    /// canvas.
+    pub(crate) fn draw(&mut self, frame: &FrameControl, raster: &PngRaster) {
+        self.dispose();
+
+        // Treat `Previous` as `Background` for the first frame.
+        let mut frame = *frame;
+        if !self.drawn && frame.dispose_op == DisposeOp::Previous {
+            frame.dispose_op = DisposeOp::Background;
+        }
+        let saved = if frame.dispose_op == DisposeOp::Previous {
+            self.save(&frame)
+        } else {
+            Vec::new()
+        };
+
+        let pixels = rgba(raster, self.bytes);
+        let px = 4 * self.bytes;
+        let (columns, rows) = self.clip(&frame);
+        for y in 0..rows {
+            let src = y * frame.width as usize * px;
+            let dst = self.offset(&frame, y);
+            for x in 0..columns {
+                let src = &pixels[src + x * px..][..px];
+                let dst = &mut self.data[dst + x * px..][..px];
+                match frame.blend_op {
+                    BlendOp::Source => dst.copy_from_slice(src),
+                    BlendOp::Over => over(dst, src, self.bytes),
+                }
+            }
+        }
+
+        self.dispose = Some((frame, saved));
+        self.drawn = true;
+    }
+
+    /// Get a copy of the current canvas contents.
+    pub(crate) fn raster(&self) -> PngRaster {
+        to_raster(self.width, self.height, self.bytes, &self.data)
+    }
+
+    /// Get the frame to draw so that the frame region of the canvas matches
+    /// the frame region of a full-size `raster`.
+    ///
+    /// `BlendOp::Over` is kept if possible (with pixels that don't change
+    /// made transparent for RGBA rasters), otherwise the frame is changed to
+    /// `BlendOp::Source`.
+    pub(crate) fn frame(
+        &self,
+        mut frame: FrameControl,
+        raster: &PngRaster,
+    ) -> (FrameControl, PngRaster) {
+        let region = raster.crop(Region::new(
+            frame.x_offset as i32,
+            frame.y_offset as i32,
+            frame.width,
+            frame.height,
+        ));
+        if frame.blend_op == BlendOp::Source {
+            return (frame, region);
+        }
+
+        let target = rgba(&region, self.bytes);
+        let mut trial = self.clone();
+        trial.dispose();
+        let candidate = match region {
+            PngRaster::Rgba8(_) | PngRaster::Rgba16(_) => {
+                let px = 4 * self.bytes;
+                let base = trial.save(&frame);
+                let mut pixels = target.clone();
+                for (pixel, base) in
+                    pixels.chunks_exact_mut(px).zip(base.chunks_exact(px))
+                {
+                    if pixel == base {
+                        pixel.fill(0);
+                    }
+                }
+                Some(to_raster(frame.width, frame.height, self.bytes, &pixels))
+            }
+            _ => None,
+        };
+        trial.draw(&frame, candidate.as_ref().unwrap_or(&region));
+        if trial.save(&frame) != target {
+            frame.blend_op = BlendOp::Source;
+            return (frame, region);
+        }
+        (frame, candidate.unwrap_or(region))
+    }
+
+    /// Change how the last drawn frame is disposed of.  Switching to
+    /// `DisposeOp::Previous` requires it to have been drawn with
+    /// `DisposeOp::Previous` (and not be the first frame).
+    pub(crate) fn redispose(&mut self, dispose_op: DisposeOp) {
+        if let Some((frame, _saved)) = self.dispose.as_mut() {
+            frame.dispose_op = dispose_op;
+        }
+    }
+
+    /// Dispose of the previous frame, then get the smallest region (x, y,
+    /// width, height) containing all of the pixels that differ from a
+    /// full-size `raster`.  If there are no differences, a 1x1 region is
+    /// returned.
+    pub(crate) fn changes(
+        &mut self,
+        raster: &PngRaster,
+    ) -> (u32, u32, u32, u32) {
+        self.dispose();
+
+        let pixels = rgba(raster, self.bytes);
+        let px = 4 * self.bytes;
+        let width = self.width as usize;
+        let (mut left, mut top) = (usize::MAX, usize::MAX);
+        let (mut right, mut bottom) = (0, 0);
+        let changed = self
+            .data
+            .chunks_exact(px)
+            .zip(pixels.chunks_exact(px))
+            .enumerate()
+            .filter(|(_, (old, new))| old != new);
+        for (i, _) in changed {
+            let (x, y) = (i % width, i / width);
+            left = left.min(x);
+            top = top.min(y);
+            right = right.max(x + 1);
+            bottom = bottom.max(y + 1);
+        }
+        if right == 0 {
+            return (0, 0, 1, 1);
+        }
+        (
+            left as u32,
+            top as u32,
+            (right - left) as u32,
+            (bottom - top) as u32,
+        )
+    }
+
+    /// Dispose of the previous frame.
+    fn dispose(&mut self) {
+        if let Some((previous, saved)) = self.dispose.take() {
+            match previous.dispose_op {
+                DisposeOp::None => {}
+                DisposeOp::Background => self.clear(&previous),
+                DisposeOp::Previous => self.restore(&previous, &saved),
+            }
+        }
+    }
+
+    /// Get the number of columns and rows of the frame region that fit on the
+    /// canvas.
+    fn clip(&self, frame: &FrameControl) -> (usize, usize) {
+        let columns = self.width.saturating_sub(frame.x_offset);
+        let rows = self.height.saturating_sub(frame.y_offset);
+        (
+            columns.min(frame.width) as usize,
+            rows.min(frame.height) as usize,
+        )
+    }
+
+    /// Get the byte offset of the start of a row of the frame region.
+    fn offset(&self, frame: &FrameControl, y: usize) -> usize {
+        let row = frame.y_offset as usize + y;
+        (row * self.width as usize + frame.x_offset as usize) * 4 * self.bytes
+    }
+
+    /// Copy out the frame region.
+    fn save(&self, frame: &FrameControl) -> Vec<u8> {
+        let (columns, rows) = self.clip(frame);
+        let len = columns * 4 * self.bytes;
+        let mut saved = Vec::with_capacity(len * rows);
+        for y in 0..rows {
+            let start = self.offset(frame, y);
+            saved.extend_from_slice(&self.data[start..][..len]);
+        }
+        saved
+    }
+
+    /// Copy saved contents back into the frame region.
+    fn restore(&mut self, frame: &FrameControl, saved: &[u8]) {
+        let (columns, _rows) = self.clip(frame);
+        let len = columns * 4 * self.bytes;
+        if len == 0 {
+            return;
+        }
+        for (y, row) in saved.chunks_exact(len).enumerate() {
+            let start = self.offset(frame, y);
+            self.data[start..][..len].copy_from_slice(row);
+        }
+    }
+
+    /// Clear the frame region to fully transparent black.
+    fn clear(&mut self, frame: &FrameControl) {
+        let (columns, rows) = self.clip(frame);
+        let len = columns * 4 * self.bytes;
+        for y in 0..rows {
+            let start = self.offset(frame, y);
+            self.data[start..][..len].fill(0);
+        }
+    }
+}
+
+/// Convert RGBA pixels with the given bytes per channel into a raster.
+fn to_raster(width: u32, height: u32, bytes: usize, data: &[u8]) -> PngRaster {
+    if bytes == 1 {
+        PngRaster::Rgba8(Raster::with_u8_buffer(width, height, data))
+    } else {
+        let mut raster = Raster::with_clear(width, height);
+        raster.as_u8_slice_mut().copy_from_slice(data);
+        PngRaster::Rgba16(raster)
+    }
+}
+
+/// Convert a raster into RGBA pixels with the given bytes per channel.
+fn rgba(raster: &PngRaster, bytes: usize) -> Vec<u8> {
+    use PngRaster::*;
+    let (samples, channels, wide): (&[u8], usize, bool) = match raster {
+        Gray8(r) => (r.as_u8_slice(), 1, false),
+        Gray16(r) => (r.as_u8_slice(), 1, true),
+        Graya8(r) => (r.as_u8_slice(), 2, false),
+        Graya16(r) => (r.as_u8_slice(), 2, true),
+        Rgb8(r) => (r.as_u8_slice(), 3, false),
+        Rgb16(r) => (r.as_u8_slice(), 3, true),
+        Rgba8(r) => (r.as_u8_slice(), 4, false),
+        Rgba16(r) => (r.as_u8_slice(), 4, true),
+        Palette(r, palette, alpha) => {
+            let mut out = Vec::with_capacity(r.pixels().len() * 4 * bytes);
+            for index in r.as_u8_slice().iter().cloned() {
+                let index = index as usize;
+                let color = palette.colors().get(index).cloned();
+                let [red, green, blue] = match color {
+                    Some(color) => [
+                        Rgb::red(color).into(),
+                        Rgb::green(color).into(),
+                        Rgb::blue(color).into(),
+                    ],
+                    None => [0; 3],
+                };
+                let alpha = alpha.get(index).cloned().unwrap_or(u8::MAX);
+                for channel in [red, green, blue, alpha] {
+                    out.extend(std::iter::repeat(channel).take(bytes));
+                }
+            }
+            return out;
+        }
+    };
+    let size = if wide { 2 } else { 1 };
+    debug_assert_eq!(size, bytes);
+    let opaque = [u8::MAX; 2];
+    let mut out = Vec::with_capacity(samples.len() / channels * 4 * bytes);
+    for pixel in samples.chunks_exact(channels * size) {
+        match channels {
+            1 | 2 => {
+                let gray = &pixel[..size];
+                out.extend_from_slice(gray);
+                out.extend_from_slice(gray);
+                out.extend_from_slice(gray);
+            }
+            _ => out.extend_from_slice(&pixel[..3 * size]),
+        }
+        match channels {
+            2 => out.extend_from_slice(&pixel[size..]),
+            4 => out.extend_from_slice(&pixel[3 * size..]),
+            _ => out.extend_from_slice(&opaque[..size]),
+        }
+    }
+    out
+}
+
+/// Alpha-composite `src` over `dst` (non-premultiplied RGBA).
+fn over(dst: &mut [u8], src: &[u8], bytes: usize) {
+    let get = |buf: &[u8], channel: usize| -> u64 {
+        match bytes {
+            1 => buf[channel].into(),
+            _ => u16::from_be_bytes([buf[channel * 2], buf[channel * 2 + 1]])
+                .into(),
+        }
+    };
+    let max: u64 = if bytes == 1 { 0xFF } else { 0xFFFF };
+    let src_alpha = get(src, 3);
+    if src_alpha == max {
+        dst.copy_from_slice(src);
+        return;
+    }
+    if src_alpha == 0 {
+        return;
+    }
+    let dst_alpha = get(dst, 3);
+    // Output alpha, scaled by `max`
+    let alpha = src_alpha * max + (max - src_alpha) * dst_alpha;
+    let mut out = [0u64; 4];
+    for (channel, value) in out.iter_mut().enumerate().take(3) {
+        let src = get(src, channel) * src_alpha * max;
+        let dst = get(dst, channel) * dst_alpha * (max - src_alpha);
+        *value = (src + dst + alpha / 2) / alpha;
+    }
+    out[3] = (alpha + max / 2) / max;
+    for (channel, value) in out.iter().cloned().enumerate() {
+        match bytes {
+            1 => dst[channel] = value as u8,
+            _ => dst[channel * 2..][..2]
+                .copy_from_slice(&(value as u16).to_be_bytes()),
+        }
+    }
+}
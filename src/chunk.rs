@@ -14,12 +14,16 @@
 //!
 //! - **Required** `ImageHeader` "IHDR"
 //! - In any order:
-//!   - **Optional** `Chromaticities`
+//!   - **Optional** `Chromaticities` "cHRM"
 //!   - **Optional** `Gamma` "gAMA"
 //!   - **Optional** `ColorProfile` "iCCP"
 //!   - **Optional** `SignificantBits` "sBIT"
 //!   - **Optional** `SRgb` "sRGB"
+//!   - **Optional** `CodePoints` "cICP"
+//!   - **Optional** `MasteringDisplay` "mDCv"
+//!   - **Optional** `ContentLightLevel` "cLLi"
 //!   - **Optional** `Physical` "pHYs"
+//!   - **Optional** `Exif` "eXIf"
 //!   - **Multiple** `SuggestedPalette` "sPLT"
 //!   - **Optional** `Time` "tIME" (If didn't appear earlier)
 //!   - **Multiple** `InternationalText` "iTXt"
@@ -30,14 +34,18 @@
 //!   - **Optional** `ImageOffset` "oFFs" (*Extension*)
 //!   - **Optional** `PixelCalibration` "pCAL" (*Extension*)
 //!   - **Optional** `SubjectPhysical` "sCAL" (*Extension*)
+//!   - **Optional** `Stereo` "sTER" (*Extension*)
 //!   - **Multiple** `GifGraphicControlExt` "gIFg" (*Extension*)
 //!   - **Multiple** `GifApplicationExt` "gIFx" (*Extension*)
 //! - **Optional** `Palette` "PLTE"
 //! - In any order:
 //!   - **Optional** `Background` "bKGD"
+//!   - **Optional** `MasteringDisplay` "mDCv" (If didn't appear before PLTE)
+//!   - **Optional** `ContentLightLevel` "cLLi" (If didn't appear before PLTE)
 //!   - **Optional** `PaletteHistogram` "hIST"
 //!   - **Optional** `Transparency` "tRNS"
 //!   - **Optional** `Physical` "pHYs" (If didn't appear before PLTE)
+//!   - **Optional** `Exif` "eXIf" (If didn't appear earlier)
 //!   - **Multiple** `SuggestedPalette` "sPLT"
 //!   - **Optional** `Time` "tIME" (If didn't appear earlier)
 //!   - **Multiple** `InternationalText` "iTXt"
@@ -51,6 +59,7 @@
 //!     earlier)
 //!   - **Optional** `SubjectPhysical` "sCAL" (*Extension*, If didn't appear
 //!     earlier)
+//!   - **Optional** `Stereo` "sTER" (*Extension*, If didn't appear earlier)
 //!   - **Multiple** `GifGraphicControlExt` "gIFg" (*Extension*)
 //!   - **Multiple** `GifApplicationExt` "gIFx" (*Extension*)
 //! - **Multiple** `ImageData` "IDAT"
@@ -65,18 +74,40 @@
 //!   - **Multiple** `GifApplicationExt` "gIFx" (*Extension*)
 //! - **Required** `ImageEnd` "IEND"
 
+pub(crate) use self::custom::Codec;
 use crate::{
     decode::{Error as DecoderError, Result as DecoderResult},
     encode::{Error as EncoderError, Result as EncoderResult},
 };
 
+mod actl;
 mod bkgd;
+mod chrm;
+mod cicp;
+mod clli;
+mod custom;
+mod exif;
+mod fctl;
+mod fdat;
+mod gama;
+mod gifg;
+mod gifx;
+mod hist;
+mod iccp;
 mod idat;
 mod iend;
 mod ihdr;
 mod itxt;
+mod mdcv;
+mod offs;
+mod pcal;
 mod phys;
 mod plte;
+mod sbit;
+mod scal;
+mod splt;
+mod srgb;
+mod ster;
 mod text;
 mod time;
 mod trns;
@@ -84,8 +115,34 @@ mod unknown;
 mod ztxt;
 
 pub use self::{
+    // APNG
+    actl::AnimationControl,
     // Optional
     bkgd::Background,
+    // Optional
+    chrm::Chromaticities,
+    // Optional
+    cicp::CodePoints,
+    // Optional
+    clli::ContentLightLevel,
+    // Custom
+    custom::{ChunkPosition, Custom, CustomChunk},
+    // Optional
+    exif::{Exif, Orientation},
+    // APNG
+    fctl::{BlendOp, DisposeOp, FrameControl},
+    // APNG
+    fdat::FrameData,
+    // Optional
+    gama::Gamma,
+    // Extension
+    gifg::GifGraphicControlExt,
+    // Extension
+    gifx::GifApplicationExt,
+    // Optional
+    hist::PaletteHistogram,
+    // Optional
+    iccp::ColorProfile,
     // Required
     idat::ImageData,
     // Required
@@ -95,10 +152,26 @@ pub use self::{
     // Optional
     itxt::InternationalText,
     // Optional
+    mdcv::MasteringDisplay,
+    // Extension
+    offs::{ImageOffset, OffsetUnit},
+    // Extension
+    pcal::{Equation, PixelCalibration},
+    // Optional
     phys::Physical,
     // Required
     plte::Palette,
     // Optional
+    sbit::SignificantBits,
+    // Extension
+    scal::{ScaleUnit, SubjectPhysical},
+    // Multiple
+    splt::{SuggestedColor, SuggestedPalette},
+    // Optional
+    srgb::{RenderingIntent, SRgb},
+    // Extension
+    ster::Stereo,
+    // Optional
     text::Text,
     // Optional
     time::Time,
@@ -112,6 +185,7 @@ pub use self::{
 
 /// A chunk within a PNG file.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Chunk {
     /// Required: Image Header
     ImageHeader(ImageHeader),
@@ -137,6 +211,51 @@ pub enum Chunk {
     Transparency(Transparency),
     /// Optional: Z text chunk.
     CompressedText(CompressedText),
+    /// Optional: Image gamma chunk.
+    Gamma(Gamma),
+    /// Optional: Primary chromaticities chunk.
+    Chromaticities(Chromaticities),
+    /// Optional: Standard RGB color space chunk.
+    SRgb(SRgb),
+    /// Optional: Embedded ICC profile chunk.
+    ColorProfile(ColorProfile),
+    /// Optional: Coding-independent code points chunk.
+    CodePoints(CodePoints),
+    /// Optional: Mastering display color volume chunk.
+    MasteringDisplay(MasteringDisplay),
+    /// Optional: Content light level chunk.
+    ContentLightLevel(ContentLightLevel),
+    /// Optional: Exchangeable image file format chunk.
+    Exif(Exif),
+    /// Optional: Significant bits chunk.
+    SignificantBits(SignificantBits),
+    /// Multiple: Suggested palette chunk.
+    SuggestedPalette(SuggestedPalette),
+    /// Optional: Palette histogram chunk.
+    PaletteHistogram(PaletteHistogram),
+
+    /// Extension: Image offset chunk.
+    ImageOffset(ImageOffset),
+    /// Extension: Pixel calibration chunk.
+    PixelCalibration(PixelCalibration),
+    /// Extension: Physical scale of image subject chunk.
+    SubjectPhysical(SubjectPhysical),
+    /// Extension: Stereo image chunk.
+    Stereo(Stereo),
+    /// Extension: GIF graphic control extension chunk.
+    GifGraphicControlExt(GifGraphicControlExt),
+    /// Extension: GIF application extension chunk.
+    GifApplicationExt(GifApplicationExt),
+
+    /// APNG: Animation control chunk.
+    AnimationControl(AnimationControl),
+    /// APNG: Frame control chunk.
+    FrameControl(FrameControl),
+    /// APNG: Frame data chunk.
+    FrameData(FrameData),
+
+    /// Chunk of a user-defined type
+    Custom(Custom),
     /// Unknown chunk
     Unknown(Unknown),
 }
@@ -149,4 +268,8 @@ impl Chunk {
     pub(super) fn is_iend(&self) -> bool {
         matches!(self, Chunk::ImageEnd(_))
     }
+
+    pub(super) fn is_fdat(&self) -> bool {
+        matches!(self, Chunk::FrameData(_))
+    }
 }
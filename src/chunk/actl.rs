@@ -0,0 +1,46 @@
+use std::io::{Read, Write};
+
+use super::{Chunk, DecoderError, EncoderError};
+use crate::{consts, decoder::Parser, encoder::Enc};
+
+/// Animation Control Chunk Data (acTL)
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct AnimationControl {
+    /// Number of frames in the animation (at least 1)
+    pub num_frames: u32,
+    /// Number of times to loop the animation (0 loops forever)
+    pub num_plays: u32,
+}
+
+impl AnimationControl {
+    pub(crate) fn write<W: Write>(
+        &self,
+        enc: &mut Enc<W>,
+    ) -> Result<(), EncoderError> {
+        if self.num_frames == 0 {
+            return Err(EncoderError::ChunkValue(consts::ANIMATION_CONTROL));
+        }
+        // 8 Bytes
+        enc.prepare(8, consts::ANIMATION_CONTROL)?;
+        enc.u32(self.num_frames)?;
+        enc.u32(self.num_plays)?;
+        enc.write_crc()
+    }
+
+    pub(crate) fn parse<R: Read>(
+        parse: &mut Parser<R>,
+    ) -> Result<Chunk, DecoderError> {
+        // 8 Bytes
+        if parse.len() != 8 {
+            return Err(DecoderError::ChunkLength(consts::ANIMATION_CONTROL));
+        }
+        let num_frames = parse.u32()?;
+        if num_frames == 0 {
+            return Err(DecoderError::ChunkValue(consts::ANIMATION_CONTROL));
+        }
+        Ok(Chunk::AnimationControl(AnimationControl {
+            num_frames,
+            num_plays: parse.u32()?,
+        }))
+    }
+}
@@ -4,7 +4,7 @@ use super::{Chunk, DecoderError, EncoderError};
 use crate::{consts, decoder::Parser, encoder::Enc};
 
 /// Suggested background color chunk (bKGD)
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Background {
     /// 8-bit palette background index
     Palette(u8),
@@ -0,0 +1,64 @@
+use std::io::{Read, Write};
+
+use super::{
+    gama::{from_fixed, to_fixed},
+    Chunk, DecoderError, EncoderError,
+};
+use crate::{consts, decoder::Parser, encoder::Enc};
+
+/// Primary chromaticities and white point chunk (cHRM)
+///
+/// Each is a CIE 1931 (x, y) chromaticity (stored as fixed-point values times
+/// 100000).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Chromaticities {
+    /// White point
+    pub white: (f64, f64),
+    /// Red primary
+    pub red: (f64, f64),
+    /// Green primary
+    pub green: (f64, f64),
+    /// Blue primary
+    pub blue: (f64, f64),
+}
+
+impl Chromaticities {
+    pub(crate) fn write<W: Write>(
+        &self,
+        enc: &mut Enc<W>,
+    ) -> Result<(), EncoderError> {
+        let mut values = [0; 8];
+        let points = [self.white, self.red, self.green, self.blue];
+        for (value, xy) in values
+            .iter_mut()
+            .zip(points.iter().flat_map(|(x, y)| [x, y]))
+        {
+            *value = to_fixed(*xy)
+                .ok_or(EncoderError::ChunkValue(consts::CHROMATICITIES))?;
+        }
+        // 32 Bytes
+        enc.prepare(32, consts::CHROMATICITIES)?;
+        for value in values {
+            enc.u32(value)?;
+        }
+        enc.write_crc()
+    }
+
+    pub(crate) fn parse<R: Read>(
+        parse: &mut Parser<R>,
+    ) -> Result<Chunk, DecoderError> {
+        // 32 Bytes
+        if parse.len() != 32 {
+            return Err(DecoderError::ChunkLength(consts::CHROMATICITIES));
+        }
+        let mut xy = || -> Result<(f64, f64), DecoderError> {
+            Ok((from_fixed(parse.u32()?), from_fixed(parse.u32()?)))
+        };
+        Ok(Chunk::Chromaticities(Chromaticities {
+            white: xy()?,
+            red: xy()?,
+            green: xy()?,
+            blue: xy()?,
+        }))
+    }
+}
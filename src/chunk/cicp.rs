@@ -0,0 +1,65 @@
+use std::io::{Read, Write};
+
+use super::{Chunk, DecoderError, EncoderError};
+use crate::{consts, decoder::Parser, encoder::Enc};
+
+/// Coding-independent code points chunk (cICP)
+///
+/// Identifies the color space of the image using the code points of ITU-T
+/// H.273, for example primaries 9 (BT.2020) with transfer function 16 (PQ)
+/// for HDR images.  Takes precedence over all other color space chunks.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CodePoints {
+    /// Color primaries
+    pub primaries: u8,
+    /// Transfer function
+    pub transfer: u8,
+    /// Matrix coefficients (must be 0, PNG only supports RGB)
+    pub matrix: u8,
+    /// Whether the samples use the full range (`false` for narrow range)
+    pub full_range: bool,
+}
+
+impl CodePoints {
+    pub(crate) fn write<W: Write>(
+        &self,
+        enc: &mut Enc<W>,
+    ) -> Result<(), EncoderError> {
+        if self.matrix != 0 {
+            return Err(EncoderError::ChunkValue(consts::CODE_POINTS));
+        }
+        // 4 Bytes
+        enc.prepare(4, consts::CODE_POINTS)?;
+        enc.u8(self.primaries)?;
+        enc.u8(self.transfer)?;
+        enc.u8(self.matrix)?;
+        enc.u8(self.full_range.into())?;
+        enc.write_crc()
+    }
+
+    pub(crate) fn parse<R: Read>(
+        parse: &mut Parser<R>,
+    ) -> Result<Chunk, DecoderError> {
+        // 4 Bytes
+        if parse.len() != 4 {
+            return Err(DecoderError::ChunkLength(consts::CODE_POINTS));
+        }
+        let primaries = parse.u8()?;
+        let transfer = parse.u8()?;
+        let matrix = parse.u8()?;
+        let full_range = match parse.u8()? {
+            0 => false,
+            1 => true,
+            _ => return Err(DecoderError::ChunkValue(consts::CODE_POINTS)),
+        };
+        if matrix != 0 {
+            return Err(DecoderError::ChunkValue(consts::CODE_POINTS));
+        }
+        Ok(Chunk::CodePoints(CodePoints {
+            primaries,
+            transfer,
+            matrix,
+            full_range,
+        }))
+    }
+}
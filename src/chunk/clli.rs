@@ -0,0 +1,48 @@
+use std::io::{Read, Write};
+
+use super::{mdcv::to_units, Chunk, DecoderError, EncoderError};
+use crate::{consts, decoder::Parser, encoder::Enc};
+
+/// Content light level chunk (cLLi)
+///
+/// Luminances are in candelas per square meter (stored in units of 0.0001).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ContentLightLevel {
+    /// Maximum content light level (MaxCLL), the brightest pixel of the
+    /// image (or of any frame)
+    pub max_content: f64,
+    /// Maximum frame-average light level (MaxFALL), the brightest average of
+    /// a frame
+    pub max_frame_average: f64,
+}
+
+impl ContentLightLevel {
+    pub(crate) fn write<W: Write>(
+        &self,
+        enc: &mut Enc<W>,
+    ) -> Result<(), EncoderError> {
+        let error = || EncoderError::ChunkValue(consts::LIGHT_LEVEL);
+        let max_content =
+            to_units(self.max_content, 10_000.0).ok_or_else(error)?;
+        let max_frame_average =
+            to_units(self.max_frame_average, 10_000.0).ok_or_else(error)?;
+        // 8 Bytes
+        enc.prepare(8, consts::LIGHT_LEVEL)?;
+        enc.u32(max_content)?;
+        enc.u32(max_frame_average)?;
+        enc.write_crc()
+    }
+
+    pub(crate) fn parse<R: Read>(
+        parse: &mut Parser<R>,
+    ) -> Result<Chunk, DecoderError> {
+        // 8 Bytes
+        if parse.len() != 8 {
+            return Err(DecoderError::ChunkLength(consts::LIGHT_LEVEL));
+        }
+        Ok(Chunk::ContentLightLevel(ContentLightLevel {
+            max_content: f64::from(parse.u32()?) / 10_000.0,
+            max_frame_average: f64::from(parse.u32()?) / 10_000.0,
+        }))
+    }
+}
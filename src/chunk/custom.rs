@@ -0,0 +1,130 @@
+use std::{any::Any, fmt, io::Write};
+
+use super::{DecoderError, EncoderError};
+use crate::{consts, encoder::Enc};
+
+/// Region of a PNG file, between its critical chunks.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ChunkPosition {
+    /// After IHDR, before PLTE
+    BeforePalette,
+    /// After PLTE, before IDAT
+    BeforeImageData,
+    /// After IDAT, before IEND
+    AfterImageData,
+}
+
+/// A user-defined chunk type.
+///
+/// Register implementations with
+/// [`Decoder::custom_chunk`](../struct.Decoder.html#method.custom_chunk) to
+/// decode them as [`Chunk::Custom`](enum.Chunk.html#variant.Custom), and with
+/// [`Encoder::custom_chunk`](../struct.Encoder.html#method.custom_chunk) to
+/// encode them.  `Steps` keeps them in the metadata, so they must be `Clone`.
+pub trait CustomChunk: Clone + fmt::Debug + Send + Sync + 'static {
+    /// Chunk name, e.g. `*b"prVw"` (with a lowercase second letter for
+    /// private chunks)
+    const NAME: [u8; 4];
+    /// Last region chunks of this type may appear in (they may also appear
+    /// in earlier regions)
+    const POSITION: ChunkPosition;
+
+    /// Parse the chunk data.
+    fn parse(data: &[u8]) -> Result<Self, DecoderError>
+    where
+        Self: Sized;
+
+    /// Write the chunk data.
+    fn write(&self) -> Result<Vec<u8>, EncoderError>;
+}
+
+/// A chunk of a user-defined type (see [`CustomChunk`]).
+pub struct Custom {
+    name: [u8; 4],
+    position: ChunkPosition,
+    chunk: Box<dyn Any + Send + Sync>,
+    write: fn(&Custom) -> Result<Vec<u8>, EncoderError>,
+    debug: fn(&Custom, &mut fmt::Formatter<'_>) -> fmt::Result,
+    clone: fn(&Custom) -> Custom,
+}
+
+impl Clone for Custom {
+    fn clone(&self) -> Self {
+        (self.clone)(self)
+    }
+}
+
+impl fmt::Debug for Custom {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (self.debug)(self, f)
+    }
+}
+
+impl Custom {
+    /// Wrap a chunk of a user-defined type.
+    pub fn new<C: CustomChunk>(chunk: C) -> Self {
+        Custom {
+            name: C::NAME,
+            position: C::POSITION,
+            chunk: Box::new(chunk),
+            write: |custom| custom.get::<C>().unwrap().write(),
+            debug: |custom, f| custom.get::<C>().unwrap().fmt(f),
+            clone: |custom| Custom::new(custom.get::<C>().unwrap().clone()),
+        }
+    }
+
+    /// Get the chunk name.
+    pub fn name(&self) -> [u8; 4] {
+        self.name
+    }
+
+    /// Get the last region the chunk may appear in.
+    pub fn position(&self) -> ChunkPosition {
+        self.position
+    }
+
+    /// Get the chunk, `None` if it isn't a `C`.
+    pub fn get<C: CustomChunk>(&self) -> Option<&C> {
+        self.chunk.downcast_ref()
+    }
+
+    /// Unwrap the chunk, or return it back if it isn't a `C`.
+    pub fn into_inner<C: CustomChunk>(self) -> Result<C, Self> {
+        if !self.chunk.is::<C>() {
+            return Err(self);
+        }
+        Ok(*self.chunk.downcast().unwrap())
+    }
+
+    pub(crate) fn write<W: Write>(
+        &self,
+        enc: &mut Enc<W>,
+    ) -> Result<(), EncoderError> {
+        let data = (self.write)(self)?;
+        if data.len() > consts::MAX_CHUNK_SIZE {
+            return Err(EncoderError::ChunkTooBig);
+        }
+        enc.prepare(data.len(), self.name)?;
+        enc.raw(&data)?;
+        enc.write_crc()
+    }
+}
+
+/// Parser of a registered user-defined chunk type.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Codec {
+    /// Chunk name
+    pub(crate) name: [u8; 4],
+    /// Parse the chunk data
+    pub(crate) parse: fn(&[u8]) -> Result<Custom, DecoderError>,
+}
+
+impl Codec {
+    /// Get the parser of a chunk type.
+    pub(crate) fn new<C: CustomChunk>() -> Self {
+        Codec {
+            name: C::NAME,
+            parse: |data| C::parse(data).map(Custom::new),
+        }
+    }
+}
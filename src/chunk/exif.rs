@@ -0,0 +1,203 @@
+use std::io::{Read, Write};
+
+use super::{Chunk, DecoderError, EncoderError};
+use crate::{consts, decoder::Parser, encoder::Enc};
+
+/// Orientation tag
+const ORIENTATION: u16 = 0x0112;
+/// Make tag
+const MAKE: u16 = 0x010F;
+/// Model tag
+const MODEL: u16 = 0x0110;
+/// Exif IFD pointer tag
+const EXIF_IFD: u16 = 0x8769;
+/// DateTimeOriginal tag (in the Exif IFD)
+const DATE_TIME_ORIGINAL: u16 = 0x9003;
+
+/// How the stored image should be transformed to display it upright (EXIF
+/// Orientation tag)
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Orientation {
+    /// Displayed as stored
+    Normal = 1,
+    /// Flipped horizontally
+    FlipHorizontal = 2,
+    /// Rotated 180°
+    Rotate180 = 3,
+    /// Flipped vertically
+    FlipVertical = 4,
+    /// Flipped along the top-left to bottom-right diagonal
+    Transpose = 5,
+    /// Rotated 90° clockwise
+    Rotate90 = 6,
+    /// Flipped along the top-right to bottom-left diagonal
+    Transverse = 7,
+    /// Rotated 270° clockwise
+    Rotate270 = 8,
+}
+
+/// Exchangeable image file format chunk (eXIf)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Exif {
+    /// EXIF data, starting with a TIFF header ("II*\0" for little endian, or
+    /// "MM\0*" for big endian)
+    pub data: Vec<u8>,
+}
+
+impl Exif {
+    /// Get the orientation (from IFD0).
+    pub fn orientation(&self) -> Option<Orientation> {
+        let tiff = Tiff::new(&self.data)?;
+        use Orientation::*;
+        match tiff.short(tiff.ifd0()?, ORIENTATION)? {
+            1 => Some(Normal),
+            2 => Some(FlipHorizontal),
+            3 => Some(Rotate180),
+            4 => Some(FlipVertical),
+            5 => Some(Transpose),
+            6 => Some(Rotate90),
+            7 => Some(Transverse),
+            8 => Some(Rotate270),
+            _ => None,
+        }
+    }
+
+    /// Get the date and time the picture was taken, formatted as
+    /// "YYYY:MM:DD HH:MM:SS" (from the Exif IFD).
+    pub fn date_time_original(&self) -> Option<String> {
+        let tiff = Tiff::new(&self.data)?;
+        let exif = tiff.long(tiff.ifd0()?, EXIF_IFD)?;
+        tiff.ascii(usize::try_from(exif).ok()?, DATE_TIME_ORIGINAL)
+    }
+
+    /// Get the camera manufacturer (from IFD0).
+    pub fn make(&self) -> Option<String> {
+        let tiff = Tiff::new(&self.data)?;
+        tiff.ascii(tiff.ifd0()?, MAKE)
+    }
+
+    /// Get the camera model (from IFD0).
+    pub fn model(&self) -> Option<String> {
+        let tiff = Tiff::new(&self.data)?;
+        tiff.ascii(tiff.ifd0()?, MODEL)
+    }
+
+    pub(crate) fn write<W: Write>(
+        &self,
+        enc: &mut Enc<W>,
+    ) -> Result<(), EncoderError> {
+        if Tiff::new(&self.data).is_none() {
+            return Err(EncoderError::ChunkValue(consts::EXIF));
+        }
+        enc.prepare(self.data.len(), consts::EXIF)?;
+        enc.raw(&self.data)?;
+        enc.write_crc()
+    }
+
+    pub(crate) fn parse<R: Read>(
+        parse: &mut Parser<R>,
+    ) -> Result<Chunk, DecoderError> {
+        let data = parse.raw()?;
+        if Tiff::new(&data).is_none() {
+            return Err(DecoderError::ChunkValue(consts::EXIF));
+        }
+        Ok(Chunk::Exif(Exif { data }))
+    }
+}
+
+/// Reader for TIFF structured data.
+struct Tiff<'a> {
+    data: &'a [u8],
+    big_endian: bool,
+}
+
+impl<'a> Tiff<'a> {
+    /// Check the byte order header, `None` if it's invalid.
+    fn new(data: &'a [u8]) -> Option<Self> {
+        let big_endian = match data.get(..4)? {
+            b"II*\0" => false,
+            b"MM\0*" => true,
+            _ => return None,
+        };
+        Some(Tiff { data, big_endian })
+    }
+
+    fn u16(&self, offset: usize) -> Option<u16> {
+        let bytes = self.data.get(offset..offset.checked_add(2)?)?;
+        let bytes = bytes.try_into().unwrap();
+        Some(if self.big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        })
+    }
+
+    fn u32(&self, offset: usize) -> Option<u32> {
+        let bytes = self.data.get(offset..offset.checked_add(4)?)?;
+        let bytes = bytes.try_into().unwrap();
+        Some(if self.big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    }
+
+    /// Get the offset of the first image file directory.
+    fn ifd0(&self) -> Option<usize> {
+        usize::try_from(self.u32(4)?).ok()
+    }
+
+    /// Find a tag in an image file directory, returning its type, count, and
+    /// the offset of its value.
+    fn entry(&self, ifd: usize, tag: u16) -> Option<(u16, usize, usize)> {
+        let count = self.u16(ifd)?;
+        (0..usize::from(count)).find_map(|i| {
+            let entry = ifd.checked_add(2 + i * 12)?;
+            if self.u16(entry)? != tag {
+                return None;
+            }
+            let kind = self.u16(entry + 2)?;
+            let count = usize::try_from(self.u32(entry + 4)?).ok()?;
+            let size = match kind {
+                // BYTE, ASCII, SBYTE, UNDEFINED
+                1 | 2 | 6 | 7 => count,
+                // SHORT, SSHORT
+                3 | 8 => count.checked_mul(2)?,
+                _ => count.checked_mul(4)?,
+            };
+            // Values that fit in 4 bytes are stored in the entry
+            let offset = if size <= 4 {
+                entry + 8
+            } else {
+                usize::try_from(self.u32(entry + 8)?).ok()?
+            };
+            Some((kind, count, offset))
+        })
+    }
+
+    /// Get a SHORT value.
+    fn short(&self, ifd: usize, tag: u16) -> Option<u16> {
+        match self.entry(ifd, tag)? {
+            (3, 1.., offset) => self.u16(offset),
+            _ => None,
+        }
+    }
+
+    /// Get a LONG value.
+    fn long(&self, ifd: usize, tag: u16) -> Option<u32> {
+        match self.entry(ifd, tag)? {
+            (4, 1.., offset) => self.u32(offset),
+            _ => None,
+        }
+    }
+
+    /// Get an ASCII value (without the NUL terminator).
+    fn ascii(&self, ifd: usize, tag: u16) -> Option<String> {
+        let (2, count, offset) = self.entry(ifd, tag)? else {
+            return None;
+        };
+        let bytes = self.data.get(offset..offset.checked_add(count)?)?;
+        let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+        String::from_utf8(bytes[..end].to_vec()).ok()
+    }
+}
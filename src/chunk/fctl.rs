@@ -0,0 +1,175 @@
+use std::io::{Read, Write};
+
+use super::{Chunk, DecoderError, EncoderError, ImageHeader, Orientation};
+use crate::{consts, decoder::Parser, encoder::Enc};
+
+/// How the frame region is treated after the frame is displayed
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum DisposeOp {
+    /// Leave the frame as is for the next frame
+    None = 0u8,
+    /// Clear the frame region to fully transparent black
+    Background = 1,
+    /// Revert the frame region to what it was before this frame
+    Previous = 2,
+}
+
+/// How the frame is drawn onto the frame region
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum BlendOp {
+    /// Overwrite the frame region with the frame
+    Source = 0u8,
+    /// Alpha-composite the frame over the frame region
+    Over = 1,
+}
+
+/// Frame Control Chunk Data (fcTL)
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct FrameControl {
+    /// Sequence number of the animation chunk, starting from 0
+    pub sequence: u32,
+    /// Width of the frame region
+    pub width: u32,
+    /// Height of the frame region
+    pub height: u32,
+    /// X position of the frame region
+    pub x_offset: u32,
+    /// Y position of the frame region
+    pub y_offset: u32,
+    /// Frame delay fraction numerator (seconds)
+    pub delay_num: u16,
+    /// Frame delay fraction denominator (seconds), 0 is treated as 100
+    pub delay_den: u16,
+    /// Frame region disposal after displaying this frame
+    pub dispose_op: DisposeOp,
+    /// Frame blending onto the frame region
+    pub blend_op: BlendOp,
+}
+
+impl FrameControl {
+    pub(crate) fn write<W: Write>(
+        &self,
+        enc: &mut Enc<W>,
+    ) -> Result<(), EncoderError> {
+        // 26 Bytes
+        enc.prepare(26, consts::FRAME_CONTROL)?;
+        enc.u32(self.sequence)?;
+        enc.u32(self.width)?;
+        enc.u32(self.height)?;
+        enc.u32(self.x_offset)?;
+        enc.u32(self.y_offset)?;
+        enc.u16(self.delay_num)?;
+        enc.u16(self.delay_den)?;
+        enc.u8(self.dispose_op as u8)?;
+        enc.u8(self.blend_op as u8)?;
+        enc.write_crc()
+    }
+
+    pub(crate) fn parse<R: Read>(
+        parse: &mut Parser<R>,
+    ) -> Result<Chunk, DecoderError> {
+        // 26 Bytes
+        if parse.len() != 26 {
+            return Err(DecoderError::ChunkLength(consts::FRAME_CONTROL));
+        }
+        let sequence = parse.u32()?;
+        parse.sequence(sequence)?;
+        let width = parse.u32()?;
+        let height = parse.u32()?;
+        let x_offset = parse.u32()?;
+        let y_offset = parse.u32()?;
+        let delay_num = parse.u16()?;
+        let delay_den = parse.u16()?;
+        let dispose_op = match parse.u8()? {
+            0 => DisposeOp::None,
+            1 => DisposeOp::Background,
+            2 => DisposeOp::Previous,
+            op => return Err(DecoderError::DisposeOp(op)),
+        };
+        let blend_op = match parse.u8()? {
+            0 => BlendOp::Source,
+            1 => BlendOp::Over,
+            op => return Err(DecoderError::BlendOp(op)),
+        };
+
+        let frame = FrameControl {
+            sequence,
+            width,
+            height,
+            x_offset,
+            y_offset,
+            delay_num,
+            delay_den,
+            dispose_op,
+            blend_op,
+        };
+        if let Some(header) = parse.header() {
+            if !frame.fits(header) {
+                return Err(DecoderError::FrameRegion);
+            }
+        }
+
+        Ok(Chunk::FrameControl(frame))
+    }
+
+    /// Check that the frame region is not empty, and is within the image.
+    pub(crate) fn fits(&self, header: &ImageHeader) -> bool {
+        let right = self.x_offset.checked_add(self.width);
+        let bottom = self.y_offset.checked_add(self.height);
+        self.width != 0
+            && self.height != 0
+            && right.is_some_and(|right| right <= header.width)
+            && bottom.is_some_and(|bottom| bottom <= header.height)
+    }
+
+    /// Check that the frame region covers the whole image.
+    pub(crate) fn is_full(&self, header: &ImageHeader) -> bool {
+        self.x_offset == 0
+            && self.y_offset == 0
+            && self.width == header.width
+            && self.height == header.height
+    }
+
+    /// Rotate and flip the frame region the same way as an image of the given
+    /// size, so it matches the oriented image.
+    pub(crate) fn orient(
+        &mut self,
+        orientation: Orientation,
+        width: u32,
+        height: u32,
+    ) {
+        use Orientation::*;
+        let (x, y) = (self.x_offset, self.y_offset);
+        // Offsets of the region's far edges from the image's far edges
+        let right = width - x - self.width;
+        let bottom = height - y - self.height;
+        let (x_offset, y_offset) = match orientation {
+            Normal => (x, y),
+            FlipHorizontal => (right, y),
+            Rotate180 => (right, bottom),
+            FlipVertical => (x, bottom),
+            Transpose => (y, x),
+            Rotate90 => (bottom, x),
+            Transverse => (bottom, right),
+            Rotate270 => (y, right),
+        };
+        self.x_offset = x_offset;
+        self.y_offset = y_offset;
+        if matches!(orientation, Transpose | Rotate90 | Transverse | Rotate270)
+        {
+            std::mem::swap(&mut self.width, &mut self.height);
+        }
+    }
+
+    /// Get the frame delay in milliseconds.
+    pub(crate) fn delay(&self) -> u32 {
+        let num = u32::from(self.delay_num);
+        let den = match self.delay_den {
+            0 => 100,
+            den => u32::from(den),
+        };
+        (num * 1000 + den / 2) / den
+    }
+}
@@ -0,0 +1,39 @@
+use std::io::{Read, Write};
+
+use crate::{
+    chunk::Chunk, consts, decode::Error as DecoderError, decoder::Parser,
+    encode::Error as EncoderError, encoder::Enc,
+};
+
+/// Frame Data Chunk Data (fdAT)
+#[derive(Debug)]
+pub struct FrameData {
+    /// Sequence number of the animation chunk
+    pub sequence: u32,
+    /// Part of a compressed ZLIB stream
+    pub data: Vec<u8>,
+}
+
+impl FrameData {
+    pub(crate) fn parse<R: Read>(
+        parse: &mut Parser<R>,
+    ) -> Result<Chunk, DecoderError> {
+        if parse.len() < 4 {
+            return Err(DecoderError::ChunkLength(consts::FRAME_DATA));
+        }
+        let sequence = parse.u32()?;
+        parse.sequence(sequence)?;
+        let data = parse.vec(parse.len() - 4)?;
+        Ok(Chunk::FrameData(FrameData { sequence, data }))
+    }
+
+    pub(crate) fn write<W: Write>(
+        &self,
+        enc: &mut Enc<W>,
+    ) -> Result<(), EncoderError> {
+        enc.prepare(self.data.len() + 4, consts::FRAME_DATA)?;
+        enc.u32(self.sequence)?;
+        enc.raw(&self.data)?;
+        enc.write_crc()
+    }
+}
@@ -0,0 +1,54 @@
+use std::io::{Read, Write};
+
+use super::{Chunk, DecoderError, EncoderError};
+use crate::{consts, decoder::Parser, encoder::Enc};
+
+/// Image gamma chunk (gAMA)
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Gamma {
+    /// Gamma the image was encoded with, e.g. `1.0 / 2.2` (stored as a
+    /// fixed-point value times 100000).  Must be greater than 0.
+    pub gamma: f64,
+}
+
+impl Gamma {
+    pub(crate) fn write<W: Write>(
+        &self,
+        enc: &mut Enc<W>,
+    ) -> Result<(), EncoderError> {
+        let gamma = match to_fixed(self.gamma) {
+            Some(gamma) if gamma != 0 => gamma,
+            _ => return Err(EncoderError::ChunkValue(consts::GAMMA)),
+        };
+        // 4 Bytes
+        enc.prepare(4, consts::GAMMA)?;
+        enc.u32(gamma)?;
+        enc.write_crc()
+    }
+
+    pub(crate) fn parse<R: Read>(
+        parse: &mut Parser<R>,
+    ) -> Result<Chunk, DecoderError> {
+        // 4 Bytes
+        if parse.len() != 4 {
+            return Err(DecoderError::ChunkLength(consts::GAMMA));
+        }
+        Ok(Chunk::Gamma(Gamma {
+            gamma: from_fixed(parse.u32()?),
+        }))
+    }
+}
+
+/// Convert a value to PNG fixed-point (times 100000), `None` if it doesn't
+/// fit.
+pub(super) fn to_fixed(value: f64) -> Option<u32> {
+    let fixed = (value * 100_000.0).round();
+    (0.0..=f64::from(u32::MAX))
+        .contains(&fixed)
+        .then_some(fixed as u32)
+}
+
+/// Convert a PNG fixed-point value (times 100000).
+pub(super) fn from_fixed(fixed: u32) -> f64 {
+    f64::from(fixed) / 100_000.0
+}
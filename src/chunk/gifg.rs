@@ -0,0 +1,68 @@
+use std::io::{Read, Write};
+
+use super::{Chunk, DecoderError, EncoderError};
+use crate::{consts, decoder::Parser, encoder::Enc};
+
+/// GIF graphic control extension chunk (gIFg)
+///
+/// The graphic control extension of an image converted from GIF.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct GifGraphicControlExt {
+    /// GIF disposal method: 0 for unspecified, 1 to leave the image in place,
+    /// 2 to restore the background color, or 3 to restore the previous image
+    /// (4 to 7 are undefined)
+    pub disposal_method: u8,
+    /// If true, wait for user input before continuing
+    pub user_input: bool,
+    /// Delay before continuing, in hundredths of a second
+    pub delay_time: u16,
+}
+
+impl GifGraphicControlExt {
+    /// Get the delay in milliseconds, like
+    /// [`Step::delay`](../struct.Step.html#structfield.delay).
+    pub fn delay(&self) -> u32 {
+        u32::from(self.delay_time) * 10
+    }
+
+    pub(crate) fn write<W: Write>(
+        &self,
+        enc: &mut Enc<W>,
+    ) -> Result<(), EncoderError> {
+        if self.disposal_method > 7 {
+            return Err(EncoderError::ChunkValue(consts::GIF_GRAPHIC_CONTROL));
+        }
+        // 4 bytes
+        enc.prepare(4, consts::GIF_GRAPHIC_CONTROL)?;
+        enc.u8(self.disposal_method)?;
+        enc.u8(self.user_input.into())?;
+        enc.u16(self.delay_time)?;
+        enc.write_crc()
+    }
+
+    pub(crate) fn parse<R: Read>(
+        parse: &mut Parser<R>,
+    ) -> Result<Chunk, DecoderError> {
+        // 4 bytes
+        if parse.len() != 4 {
+            return Err(DecoderError::ChunkLength(consts::GIF_GRAPHIC_CONTROL));
+        }
+        let error = || DecoderError::ChunkValue(consts::GIF_GRAPHIC_CONTROL);
+        let disposal_method = parse.u8()?;
+        if disposal_method > 7 {
+            return Err(error());
+        }
+        let user_input = match parse.u8()? {
+            0 => false,
+            1 => true,
+            _ => return Err(error()),
+        };
+        let delay_time = parse.u16()?;
+
+        Ok(Chunk::GifGraphicControlExt(GifGraphicControlExt {
+            disposal_method,
+            user_input,
+            delay_time,
+        }))
+    }
+}
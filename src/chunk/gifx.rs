@@ -0,0 +1,50 @@
+use std::io::{Read, Write};
+
+use super::{Chunk, DecoderError, EncoderError};
+use crate::{consts, decoder::Parser, encoder::Enc};
+
+/// GIF application extension chunk (gIFx)
+///
+/// An application extension of an image converted from GIF, such as the
+/// "NETSCAPE" looping extension.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GifApplicationExt {
+    /// Application identifier (e.g. `*b"NETSCAPE"`)
+    pub identifier: [u8; 8],
+    /// Application authentication code (e.g. `*b"2.0"`)
+    pub authentication: [u8; 3],
+    /// Application data
+    pub data: Vec<u8>,
+}
+
+impl GifApplicationExt {
+    pub(crate) fn write<W: Write>(
+        &self,
+        enc: &mut Enc<W>,
+    ) -> Result<(), EncoderError> {
+        // 8 byte identifier, 3 byte code, then data
+        enc.prepare(self.data.len() + 11, consts::GIF_APPLICATION)?;
+        enc.raw(&self.identifier)?;
+        enc.raw(&self.authentication)?;
+        enc.raw(&self.data)?;
+        enc.write_crc()
+    }
+
+    pub(crate) fn parse<R: Read>(
+        parse: &mut Parser<R>,
+    ) -> Result<Chunk, DecoderError> {
+        let len = parse
+            .len()
+            .checked_sub(11)
+            .ok_or(DecoderError::ChunkLength(consts::GIF_APPLICATION))?;
+        let identifier = parse.vec(8)?.try_into().unwrap();
+        let authentication = parse.vec(3)?.try_into().unwrap();
+        let data = parse.vec(len)?;
+
+        Ok(Chunk::GifApplicationExt(GifApplicationExt {
+            identifier,
+            authentication,
+            data,
+        }))
+    }
+}
@@ -0,0 +1,45 @@
+use std::io::{Read, Write};
+
+use super::{Chunk, DecoderError, EncoderError};
+use crate::{consts, decoder::Parser, encoder::Enc};
+
+/// Palette histogram chunk (hIST)
+///
+/// How often each palette entry appears in the image, for viewers that can't
+/// display all of the palette's colors.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PaletteHistogram {
+    /// Frequency of each palette entry, relative to the other entries (0 only
+    /// if the entry isn't used).  Must have the same length as the palette.
+    pub frequencies: Vec<u16>,
+}
+
+impl PaletteHistogram {
+    pub(crate) fn parse<R: Read>(
+        parse: &mut Parser<R>,
+    ) -> Result<Chunk, DecoderError> {
+        let len = parse.len();
+        if len == 0 || len % 2 != 0 || len > 512 {
+            return Err(DecoderError::ChunkLength(consts::PALETTE_HISTOGRAM));
+        }
+        let frequencies = (0..len / 2)
+            .map(|_| parse.u16())
+            .collect::<Result<_, _>>()?;
+
+        Ok(Chunk::PaletteHistogram(PaletteHistogram { frequencies }))
+    }
+
+    pub(crate) fn write<W: Write>(
+        &self,
+        enc: &mut Enc<W>,
+    ) -> Result<(), EncoderError> {
+        if self.frequencies.is_empty() || self.frequencies.len() > 256 {
+            return Err(EncoderError::ChunkValue(consts::PALETTE_HISTOGRAM));
+        }
+        enc.prepare(self.frequencies.len() * 2, consts::PALETTE_HISTOGRAM)?;
+        for frequency in self.frequencies.iter() {
+            enc.u16(*frequency)?;
+        }
+        enc.write_crc()
+    }
+}
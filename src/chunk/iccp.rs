@@ -0,0 +1,59 @@
+use std::io::{Read, Write};
+
+use super::{text::latin1, Chunk, DecoderError, EncoderError};
+use crate::{consts, decoder::Parser, encoder::Enc, zlib};
+
+/// Embedded ICC profile chunk (iCCP)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ColorProfile {
+    /// Profile name, ISO 8859-1 (Latin-1).  Minimum of 1 character, and
+    /// maximum 79 characters long.
+    pub name: String,
+    /// The ICC profile (uncompressed)
+    pub profile: Vec<u8>,
+}
+
+impl ColorProfile {
+    pub(crate) fn write<W: Write>(
+        &self,
+        enc: &mut Enc<W>,
+    ) -> Result<(), EncoderError> {
+        // Checks
+        let name = latin1(&self.name)?;
+        if name.is_empty() || name.len() > 79 {
+            return Err(EncoderError::KeySize(name.len()));
+        }
+
+        // Compress profile
+        let mut zdata = Vec::new();
+        zlib::compress(&mut zdata, &self.profile, enc.level());
+
+        // Encode Chunk
+        enc.prepare(name.len() + 2 + zdata.len(), consts::COLOR_PROFILE)?;
+        enc.raw(&name)?;
+        enc.u8(0)?;
+        enc.u8(0)?; // Compression Method
+        enc.raw(&zdata)?;
+        enc.write_crc()
+    }
+
+    pub(crate) fn parse<R: Read>(
+        parse: &mut Parser<R>,
+    ) -> Result<Chunk, DecoderError> {
+        let name = parse.latin1_str()?;
+        let name_len = name.chars().count();
+        if name_len == 0 || name_len > 79 {
+            return Err(DecoderError::KeySize(name_len));
+        }
+        if parse.len() < name_len + 2 {
+            return Err(DecoderError::ChunkLength(consts::COLOR_PROFILE));
+        }
+        if parse.u8()? != 0 {
+            return Err(DecoderError::CompressionMethod);
+        }
+        let zdata = parse.vec(parse.len() - (name_len + 2))?;
+        let profile = zlib::decompress(&zdata)?;
+
+        Ok(Chunk::ColorProfile(ColorProfile { name, profile }))
+    }
+}
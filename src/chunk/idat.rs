@@ -2,7 +2,7 @@ use std::io::{Read, Write};
 
 use crate::{
     chunk::Chunk, consts, decode::Result as DecoderResult, decoder::Parser,
-    encode::Error as EncoderError, encoder::Enc, zlib,
+    encode::Error as EncoderError, encoder::Enc,
 };
 
 /// Image Data Chunk Data (IDAT)
@@ -24,17 +24,15 @@ impl ImageData {
         &self,
         enc: &mut Enc<W>,
     ) -> Result<(), EncoderError> {
-        // FIXME: Should already be compressed.
-        let mut zlib = Vec::new();
-        zlib::compress(&mut zlib, self.data.as_slice(), enc.level());
-
-        //
-        enc.prepare(zlib.len(), consts::IMAGE_DATA)?;
-        enc.raw(&zlib)?;
+        enc.prepare(self.data.len(), consts::IMAGE_DATA)?;
+        enc.raw(&self.data)?;
         enc.write_crc()
     }
 
-    /// Construct from raw uncompressed image data.
+    /// Construct from compressed image data (part of a ZLIB stream).
+    ///
+    /// The data is written as is (previously, it was compressed again when
+    /// written).
     pub fn with_data(data: Vec<u8>) -> ImageData {
         ImageData { data }
     }
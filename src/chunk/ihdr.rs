@@ -143,13 +143,16 @@ impl ImageHeader {
             _ => return Err(DecoderError::InterlaceMethod),
         };
 
-        Ok(Chunk::ImageHeader(Self {
+        let header = Self {
             width,
             height,
             color_type,
             bit_depth,
             interlace,
-        }))
+        };
+        parse.set_header(header);
+
+        Ok(Chunk::ImageHeader(header))
     }
 
     /// get the total amount of bits per pixel, based on colortype and bitdepth
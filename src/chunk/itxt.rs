@@ -1,12 +1,14 @@
 use std::io::{Read, Write};
 
-use super::Chunk;
+use super::{text::latin1, Chunk};
 use crate::{
     consts, decode::Error as DecoderError, decoder::Parser,
     encode::Error as EncoderError, encoder::Enc, zlib,
 };
 
 /// International Text Chunk Data (iTXt)
+///
+/// The keyword is ISO 8859-1 (Latin-1) like tEXt, and the text is UTF-8.
 #[derive(Clone, Debug)]
 pub struct InternationalText {
     /// A keyword that gives a short description of what the text in `val`
@@ -29,9 +31,10 @@ impl InternationalText {
     pub(crate) fn parse<R: Read>(
         parse: &mut Parser<R>,
     ) -> Result<Chunk, DecoderError> {
-        let key = parse.str()?;
-        if key.is_empty() || key.len() > 79 {
-            return Err(DecoderError::KeySize(key.len()));
+        let key = parse.latin1_str()?;
+        let key_len = key.chars().count();
+        if key_len == 0 || key_len > 79 {
+            return Err(DecoderError::KeySize(key_len));
         }
         let compressed = parse.u8()? != 0;
         if parse.u8()? != 0 {
@@ -40,7 +43,7 @@ impl InternationalText {
         let langtag = parse.str()?;
         let transkey = parse.str()?;
         let data = parse.vec(
-            parse.len() - (key.len() + langtag.len() + transkey.len() + 5),
+            parse.len() - (key_len + langtag.len() + transkey.len() + 5),
         )?;
 
         let val = if compressed {
@@ -62,9 +65,9 @@ impl InternationalText {
         enc: &mut Enc<W>,
     ) -> Result<(), EncoderError> {
         // Checks
-        let k_len = self.key.len();
-        if !(1..=79).contains(&k_len) {
-            return Err(EncoderError::KeySize(k_len));
+        let key = latin1(&self.key)?;
+        if !(1..=79).contains(&key.len()) {
+            return Err(EncoderError::KeySize(key.len()));
         }
 
         // Maybe compress
@@ -83,10 +86,11 @@ impl InternationalText {
             self.val.len()
         };
         enc.prepare(
-            self.key.len() + self.langtag.len() + self.transkey.len() + len + 5,
+            key.len() + self.langtag.len() + self.transkey.len() + len + 5,
             consts::ITEXT,
         )?;
-        enc.str(&self.key)?;
+        enc.raw(&key)?;
+        enc.u8(0)?;
         enc.u8(self.compressed as u8)?;
         enc.u8(0)?;
         enc.str(&self.langtag)?;
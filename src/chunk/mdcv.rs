@@ -0,0 +1,89 @@
+use std::io::{Read, Write};
+
+use super::{Chunk, DecoderError, EncoderError};
+use crate::{consts, decoder::Parser, encoder::Enc};
+
+/// Mastering display color volume chunk (mDCv)
+///
+/// Describes the display the image was mastered on.  Chromaticities are CIE
+/// 1931 (x, y) (stored in units of 0.00002), and luminances are in candelas
+/// per square meter (stored in units of 0.0001).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MasteringDisplay {
+    /// Red primary
+    pub red: (f64, f64),
+    /// Green primary
+    pub green: (f64, f64),
+    /// Blue primary
+    pub blue: (f64, f64),
+    /// White point
+    pub white: (f64, f64),
+    /// Maximum luminance
+    pub max_luminance: f64,
+    /// Minimum luminance
+    pub min_luminance: f64,
+}
+
+impl MasteringDisplay {
+    pub(crate) fn write<W: Write>(
+        &self,
+        enc: &mut Enc<W>,
+    ) -> Result<(), EncoderError> {
+        let error = || EncoderError::ChunkValue(consts::MASTERING_DISPLAY);
+        let mut chromaticities = [0; 8];
+        let points = [self.red, self.green, self.blue, self.white];
+        for (value, xy) in chromaticities
+            .iter_mut()
+            .zip(points.iter().flat_map(|(x, y)| [x, y]))
+        {
+            *value = to_units(*xy, 50_000.0)
+                .and_then(|value| u16::try_from(value).ok())
+                .ok_or_else(error)?;
+        }
+        let max_luminance =
+            to_units(self.max_luminance, 10_000.0).ok_or_else(error)?;
+        let min_luminance =
+            to_units(self.min_luminance, 10_000.0).ok_or_else(error)?;
+        // 24 Bytes
+        enc.prepare(24, consts::MASTERING_DISPLAY)?;
+        for value in chromaticities {
+            enc.u16(value)?;
+        }
+        enc.u32(max_luminance)?;
+        enc.u32(min_luminance)?;
+        enc.write_crc()
+    }
+
+    pub(crate) fn parse<R: Read>(
+        parse: &mut Parser<R>,
+    ) -> Result<Chunk, DecoderError> {
+        // 24 Bytes
+        if parse.len() != 24 {
+            return Err(DecoderError::ChunkLength(consts::MASTERING_DISPLAY));
+        }
+        let mut xy = || -> Result<(f64, f64), DecoderError> {
+            Ok((
+                f64::from(parse.u16()?) / 50_000.0,
+                f64::from(parse.u16()?) / 50_000.0,
+            ))
+        };
+        let (red, green, blue, white) = (xy()?, xy()?, xy()?, xy()?);
+        Ok(Chunk::MasteringDisplay(MasteringDisplay {
+            red,
+            green,
+            blue,
+            white,
+            max_luminance: f64::from(parse.u32()?) / 10_000.0,
+            min_luminance: f64::from(parse.u32()?) / 10_000.0,
+        }))
+    }
+}
+
+/// Convert a value to a whole number of units (`units` per 1), `None` if it
+/// doesn't fit in 32 bits.
+pub(super) fn to_units(value: f64, units: f64) -> Option<u32> {
+    let units = (value * units).round();
+    (0.0..=f64::from(u32::MAX))
+        .contains(&units)
+        .then_some(units as u32)
+}
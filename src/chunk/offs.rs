@@ -0,0 +1,57 @@
+use std::io::{Read, Write};
+
+use super::{Chunk, DecoderError, EncoderError};
+use crate::{consts, decoder::Parser, encoder::Enc};
+
+/// Unit of an image offset
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum OffsetUnit {
+    /// Pixels
+    Pixel = 0,
+    /// Micrometers (10^-6 meters)
+    Micrometer = 1,
+}
+
+/// Image offset chunk (oFFs)
+///
+/// The position of the image on a larger page, for example when printing.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ImageOffset {
+    /// Offset of the left edge of the image from the left edge of the page
+    pub x: i32,
+    /// Offset of the top edge of the image from the top edge of the page
+    pub y: i32,
+    /// Unit of the offsets
+    pub unit: OffsetUnit,
+}
+
+impl ImageOffset {
+    pub(crate) fn write<W: Write>(
+        &self,
+        enc: &mut Enc<W>,
+    ) -> Result<(), EncoderError> {
+        enc.prepare(9, consts::IMAGE_OFFSET)?;
+        enc.u32(self.x as u32)?;
+        enc.u32(self.y as u32)?;
+        enc.u8(self.unit as u8)?;
+        enc.write_crc()
+    }
+
+    pub(crate) fn parse<R: Read>(
+        parse: &mut Parser<R>,
+    ) -> Result<Chunk, DecoderError> {
+        // 9 bytes
+        if parse.len() != 9 {
+            return Err(DecoderError::ChunkLength(consts::IMAGE_OFFSET));
+        }
+        let x = parse.u32()? as i32;
+        let y = parse.u32()? as i32;
+        let unit = match parse.u8()? {
+            0 => OffsetUnit::Pixel,
+            1 => OffsetUnit::Micrometer,
+            _ => return Err(DecoderError::ChunkValue(consts::IMAGE_OFFSET)),
+        };
+
+        Ok(Chunk::ImageOffset(ImageOffset { x, y, unit }))
+    }
+}
@@ -0,0 +1,172 @@
+use std::io::{Read, Write};
+
+use super::{scal::float, text::latin1, Chunk, DecoderError, EncoderError};
+use crate::{consts, decoder::Parser, encoder::Enc};
+
+/// Equation mapping stored sample values to physical values.
+///
+/// In each, `x` is the stored sample value mapped linearly to the range `x0`
+/// to `x1`, and `p0` to `p3` are the parameters.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Equation {
+    /// `p0 + p1 * x / (x1 - x0)`
+    Linear = 0,
+    /// `p0 + p1 * e ^ (p2 * x / (x1 - x0))`
+    Exponential = 1,
+    /// `p0 + p1 * p3 ^ (p2 * x / (x1 - x0))`
+    ArbitraryExponential = 2,
+    /// `p0 + p1 * sinh(p2 * x / (x1 - x0))`
+    Hyperbolic = 3,
+}
+
+impl Equation {
+    /// Get the number of parameters of the equation.
+    pub fn params(self) -> usize {
+        match self {
+            Equation::Linear => 2,
+            Equation::Exponential => 3,
+            Equation::ArbitraryExponential | Equation::Hyperbolic => 4,
+        }
+    }
+}
+
+/// Pixel calibration chunk (pCAL)
+///
+/// How stored sample values map to physical values, such as temperatures or
+/// elevations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PixelCalibration {
+    /// Name of the calibration.  Latin-1, minimum of 1 character and maximum
+    /// 79 characters long.
+    pub name: String,
+    /// Value the minimum stored sample value maps to
+    pub x0: i32,
+    /// Value the maximum stored sample value maps to (must differ from `x0`)
+    pub x1: i32,
+    /// Equation from `x` to physical values
+    pub equation: Equation,
+    /// Unit of the physical values (Latin-1, may be empty)
+    pub unit: String,
+    /// Parameters of the equation, as ASCII floating-point numbers (e.g.
+    /// "1.5e-3"), as many as [`Equation::params`]
+    pub params: Vec<String>,
+}
+
+impl PixelCalibration {
+    /// Get the physical value of a stored sample value from 0 to `max` (e.g.
+    /// 255 for 8-bit samples), `None` if the calibration is invalid.
+    pub fn physical_value(&self, sample: u16, max: u16) -> Option<f64> {
+        let params = self
+            .params
+            .iter()
+            .map(|param| float(param))
+            .collect::<Option<Vec<_>>>()?;
+        if params.len() != self.equation.params()
+            || self.x0 == self.x1
+            || max == 0
+        {
+            return None;
+        }
+        // Integer mapping to `x0..=x1`, rounded to nearest
+        let range = i64::from(self.x1) - i64::from(self.x0);
+        let (sample, max) = (i64::from(sample), i64::from(max));
+        let x = (sample * range + max / 2) / max + i64::from(self.x0);
+        let x = x as f64 / range as f64;
+        Some(match (self.equation, params.as_slice()) {
+            (Equation::Linear, [p0, p1]) => p0 + p1 * x,
+            (Equation::Exponential, [p0, p1, p2]) => p0 + p1 * (p2 * x).exp(),
+            (Equation::ArbitraryExponential, [p0, p1, p2, p3]) => {
+                p0 + p1 * p3.powf(p2 * x)
+            }
+            (Equation::Hyperbolic, [p0, p1, p2, _]) => {
+                p0 + p1 * (p2 * x).sinh()
+            }
+            _ => unreachable!(),
+        })
+    }
+
+    pub(crate) fn write<W: Write>(
+        &self,
+        enc: &mut Enc<W>,
+    ) -> Result<(), EncoderError> {
+        // Checks
+        let name = latin1(&self.name)?;
+        if name.is_empty() || name.len() > 79 {
+            return Err(EncoderError::KeySize(name.len()));
+        }
+        let unit = latin1(&self.unit)?;
+        if self.x0 == self.x1
+            || self.params.len() != self.equation.params()
+            || self.params.iter().any(|param| float(param).is_none())
+        {
+            return Err(EncoderError::ChunkValue(consts::PIXEL_CALIBRATION));
+        }
+        let params = self.params.join("\0");
+
+        // Null-terminated name, x0, x1, equation type, number of parameters,
+        // null-terminated unit, then null-separated parameters
+        enc.prepare(
+            name.len() + unit.len() + params.len() + 12,
+            consts::PIXEL_CALIBRATION,
+        )?;
+        enc.raw(&name)?;
+        enc.u8(0)?;
+        enc.u32(self.x0 as u32)?;
+        enc.u32(self.x1 as u32)?;
+        enc.u8(self.equation as u8)?;
+        enc.u8(self.params.len() as u8)?;
+        enc.raw(&unit)?;
+        enc.u8(0)?;
+        enc.raw(params.as_bytes())?;
+        enc.write_crc()
+    }
+
+    pub(crate) fn parse<R: Read>(
+        parse: &mut Parser<R>,
+    ) -> Result<Chunk, DecoderError> {
+        let error = || DecoderError::ChunkValue(consts::PIXEL_CALIBRATION);
+        let name = parse.latin1_str()?;
+        let name_len = name.chars().count();
+        if name_len == 0 || name_len > 79 {
+            return Err(DecoderError::KeySize(name_len));
+        }
+        let x0 = parse.u32()? as i32;
+        let x1 = parse.u32()? as i32;
+        let equation = match parse.u8()? {
+            0 => Equation::Linear,
+            1 => Equation::Exponential,
+            2 => Equation::ArbitraryExponential,
+            3 => Equation::Hyperbolic,
+            _ => return Err(error()),
+        };
+        if x0 == x1 || usize::from(parse.u8()?) != equation.params() {
+            return Err(error());
+        }
+        let unit = parse.latin1_str()?;
+        let len = parse
+            .len()
+            .checked_sub(name_len + unit.chars().count() + 12)
+            .ok_or(DecoderError::ChunkLength(consts::PIXEL_CALIBRATION))?;
+        let params = parse
+            .vec(len)?
+            .split(|byte| *byte == 0)
+            .map(|param| {
+                let param = std::str::from_utf8(param).ok()?;
+                float(param).map(|_| param.to_string())
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(error)?;
+        if params.len() != equation.params() {
+            return Err(error());
+        }
+
+        Ok(Chunk::PixelCalibration(PixelCalibration {
+            name,
+            x0,
+            x1,
+            equation,
+            unit,
+            params,
+        }))
+    }
+}
@@ -4,7 +4,7 @@ use super::{Chunk, DecoderError, EncoderError};
 use crate::{consts, decoder::Parser, encoder::Enc};
 
 /// Physical dimensions chunk (pHYs)
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Physical {
     /// Pixels per unit: X dimension
     pub ppu_x: u32,
@@ -0,0 +1,105 @@
+use std::io::{Read, Write};
+
+use super::{Chunk, ColorType, DecoderError, EncoderError};
+use crate::{consts, decoder::Parser, encoder::Enc};
+
+/// Significant bits chunk (sBIT)
+///
+/// The number of bits of each channel that were significant in the original
+/// data, e.g. 12 for 12-bit data stored in a 16-bit image.  Each must be at
+/// least 1, and at most the bit depth of the image (8 for palette images).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SignificantBits {
+    /// Gray
+    Gray(u8),
+    /// Gray and alpha
+    GrayAlpha(u8, u8),
+    /// Red, green and blue (also for palette colors)
+    Rgb(u8, u8, u8),
+    /// Red, green, blue and alpha
+    Rgba(u8, u8, u8, u8),
+}
+
+impl SignificantBits {
+    /// Get the significant bits of a channel (0 to 2 for red, green and blue,
+    /// or gray, and 3 for alpha), `None` if not specified.
+    pub(crate) fn bits(&self, channel: usize) -> Option<u8> {
+        use SignificantBits::*;
+        match (*self, channel) {
+            (Gray(gray) | GrayAlpha(gray, _), 0..=2) => Some(gray),
+            (Rgb(red, ..) | Rgba(red, ..), 0) => Some(red),
+            (Rgb(_, green, _) | Rgba(_, green, ..), 1) => Some(green),
+            (Rgb(.., blue) | Rgba(_, _, blue, _), 2) => Some(blue),
+            (GrayAlpha(_, alpha) | Rgba(.., alpha), 3) => Some(alpha),
+            _ => None,
+        }
+    }
+
+    /// Get the significant bits of all channels, in the order they're stored.
+    fn channels(&self) -> Vec<u8> {
+        use SignificantBits::*;
+        match *self {
+            Gray(gray) => vec![gray],
+            GrayAlpha(gray, alpha) => vec![gray, alpha],
+            Rgb(red, green, blue) => vec![red, green, blue],
+            Rgba(red, green, blue, alpha) => vec![red, green, blue, alpha],
+        }
+    }
+
+    /// Check that the channels match the color type, and that each fits in
+    /// the bit depth.
+    pub(crate) fn is_valid(
+        &self,
+        color_type: ColorType,
+        bit_depth: u8,
+    ) -> bool {
+        use SignificantBits::*;
+        let max = match color_type {
+            ColorType::Palette => 8,
+            _ => bit_depth,
+        };
+        matches!(
+            (self, color_type),
+            (Gray(_), ColorType::Grey)
+                | (GrayAlpha(..), ColorType::GreyAlpha)
+                | (Rgb(..), ColorType::Rgb | ColorType::Palette)
+                | (Rgba(..), ColorType::Rgba)
+        ) && self.channels().iter().all(|bits| (1..=max).contains(bits))
+    }
+
+    pub(crate) fn parse<R: Read>(
+        parse: &mut Parser<R>,
+    ) -> Result<Chunk, DecoderError> {
+        use SignificantBits::*;
+        let len = parse.len();
+        let mut bits = || -> Result<u8, DecoderError> {
+            match parse.u8()? {
+                0 => Err(DecoderError::ChunkValue(consts::SIGNIFICANT_BITS)),
+                bits => Ok(bits),
+            }
+        };
+        let significant_bits = match len {
+            1 => Gray(bits()?),
+            2 => GrayAlpha(bits()?, bits()?),
+            3 => Rgb(bits()?, bits()?, bits()?),
+            4 => Rgba(bits()?, bits()?, bits()?, bits()?),
+            _ => {
+                return Err(DecoderError::ChunkLength(consts::SIGNIFICANT_BITS))
+            }
+        };
+        Ok(Chunk::SignificantBits(significant_bits))
+    }
+
+    pub(crate) fn write<W: Write>(
+        &self,
+        enc: &mut Enc<W>,
+    ) -> Result<(), EncoderError> {
+        let channels = self.channels();
+        if channels.iter().any(|bits| !(1..=16).contains(bits)) {
+            return Err(EncoderError::ChunkValue(consts::SIGNIFICANT_BITS));
+        }
+        enc.prepare(channels.len(), consts::SIGNIFICANT_BITS)?;
+        enc.raw(&channels)?;
+        enc.write_crc()
+    }
+}
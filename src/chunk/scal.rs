@@ -0,0 +1,110 @@
+use std::io::{Read, Write};
+
+use super::{Chunk, DecoderError, EncoderError};
+use crate::{consts, decoder::Parser, encoder::Enc};
+
+/// Unit of the physical scale of an image subject
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ScaleUnit {
+    /// Meters
+    Meter = 1,
+    /// Radians
+    Radian = 2,
+}
+
+/// Physical scale of image subject chunk (sCAL)
+///
+/// The size of the area of the subject each pixel covers, for example in maps
+/// or microscope images.  Unlike pHYs, this describes the subject rather than
+/// how the image should be displayed.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SubjectPhysical {
+    /// Unit of the pixel width and height
+    pub unit: ScaleUnit,
+    /// Width of a pixel (must be positive)
+    pub width: f64,
+    /// Height of a pixel (must be positive)
+    pub height: f64,
+}
+
+impl SubjectPhysical {
+    pub(crate) fn write<W: Write>(
+        &self,
+        enc: &mut Enc<W>,
+    ) -> Result<(), EncoderError> {
+        let valid = |value: f64| value.is_finite() && value > 0.0;
+        if !valid(self.width) || !valid(self.height) {
+            return Err(EncoderError::ChunkValue(consts::SUBJECT_PHYSICAL));
+        }
+        let (width, height) = (self.width.to_string(), self.height.to_string());
+
+        // Unit, null-terminated width, then height
+        enc.prepare(width.len() + height.len() + 2, consts::SUBJECT_PHYSICAL)?;
+        enc.u8(self.unit as u8)?;
+        enc.raw(width.as_bytes())?;
+        enc.u8(0)?;
+        enc.raw(height.as_bytes())?;
+        enc.write_crc()
+    }
+
+    pub(crate) fn parse<R: Read>(
+        parse: &mut Parser<R>,
+    ) -> Result<Chunk, DecoderError> {
+        let error = || DecoderError::ChunkValue(consts::SUBJECT_PHYSICAL);
+        let len = parse.len();
+        if len < 4 {
+            return Err(DecoderError::ChunkLength(consts::SUBJECT_PHYSICAL));
+        }
+        let unit = match parse.u8()? {
+            1 => ScaleUnit::Meter,
+            2 => ScaleUnit::Radian,
+            _ => return Err(error()),
+        };
+        let values = parse.vec(len - 1)?;
+        let mut values = values.split(|byte| *byte == 0).map(|value| {
+            let value = float(std::str::from_utf8(value).ok()?)?;
+            (value > 0.0).then_some(value)
+        });
+        let (Some(Some(width)), Some(Some(height)), None) =
+            (values.next(), values.next(), values.next())
+        else {
+            return Err(error());
+        };
+
+        Ok(Chunk::SubjectPhysical(SubjectPhysical {
+            unit,
+            width,
+            height,
+        }))
+    }
+}
+
+/// Parse a PNG ASCII floating-point number: an optional sign, digits with an
+/// optional decimal point, and an optional exponent (e.g. "-1.5E+3").
+pub(super) fn float(text: &str) -> Option<f64> {
+    let digits =
+        |text: &str| text.bytes().take_while(u8::is_ascii_digit).count();
+    let mut rest = text.strip_prefix(['+', '-']).unwrap_or(text);
+    let integer = digits(rest);
+    rest = &rest[integer..];
+    let mut fraction = 0;
+    if let Some(after) = rest.strip_prefix('.') {
+        fraction = digits(after);
+        rest = &after[fraction..];
+    }
+    if integer + fraction == 0 {
+        return None;
+    }
+    if let Some(after) = rest.strip_prefix(['e', 'E']) {
+        let after = after.strip_prefix(['+', '-']).unwrap_or(after);
+        let exponent = digits(after);
+        if exponent == 0 {
+            return None;
+        }
+        rest = &after[exponent..];
+    }
+    if !rest.is_empty() {
+        return None;
+    }
+    text.parse().ok()
+}
@@ -0,0 +1,131 @@
+use std::io::{Read, Write};
+
+use super::{text::latin1, Chunk, DecoderError, EncoderError};
+use crate::{consts, decoder::Parser, encoder::Enc};
+
+/// An entry of a suggested palette.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SuggestedColor {
+    /// Red sample
+    pub red: u16,
+    /// Green sample
+    pub green: u16,
+    /// Blue sample
+    pub blue: u16,
+    /// Alpha sample (0 is fully transparent)
+    pub alpha: u16,
+    /// How often the color appears in the image, relative to the other
+    /// entries (0 if unknown)
+    pub frequency: u16,
+}
+
+/// Suggested palette chunk (sPLT)
+///
+/// A palette for viewers that can't display all of the image's colors, with
+/// how often each color appears.  Samples are not premultiplied by alpha.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SuggestedPalette {
+    /// Name of the palette, which must be unique within the file.  Latin-1,
+    /// minimum of 1 character and maximum 79 characters long.
+    pub name: String,
+    /// Bits per sample of the entries, 8 or 16 (regardless of the image bit
+    /// depth)
+    pub sample_depth: u8,
+    /// Palette entries, in decreasing order of frequency
+    pub entries: Vec<SuggestedColor>,
+}
+
+impl SuggestedPalette {
+    pub(crate) fn parse<R: Read>(
+        parse: &mut Parser<R>,
+    ) -> Result<Chunk, DecoderError> {
+        let name = parse.latin1_str()?;
+        let name_len = name.chars().count();
+        if name_len == 0 || name_len > 79 {
+            return Err(DecoderError::KeySize(name_len));
+        }
+        let sample_depth = parse.u8()?;
+        let entry_len = match sample_depth {
+            8 => 6,
+            16 => 10,
+            _ => {
+                return Err(DecoderError::ChunkValue(consts::SUGGESTED_PALETTE))
+            }
+        };
+        let len = parse
+            .len()
+            .checked_sub(name_len + 2)
+            .ok_or(DecoderError::ChunkLength(consts::SUGGESTED_PALETTE))?;
+        if len % entry_len != 0 {
+            return Err(DecoderError::ChunkLength(consts::SUGGESTED_PALETTE));
+        }
+        let mut entries = Vec::with_capacity(len / entry_len);
+        for _ in 0..len / entry_len {
+            let mut sample = || match sample_depth {
+                8 => parse.u8().map(u16::from),
+                _ => parse.u16(),
+            };
+            let (red, green, blue, alpha) =
+                (sample()?, sample()?, sample()?, sample()?);
+            entries.push(SuggestedColor {
+                red,
+                green,
+                blue,
+                alpha,
+                frequency: parse.u16()?,
+            });
+        }
+
+        Ok(Chunk::SuggestedPalette(SuggestedPalette {
+            name,
+            sample_depth,
+            entries,
+        }))
+    }
+
+    pub(crate) fn write<W: Write>(
+        &self,
+        enc: &mut Enc<W>,
+    ) -> Result<(), EncoderError> {
+        // Checks
+        let name = latin1(&self.name)?;
+        if name.is_empty() || name.len() > 79 {
+            return Err(EncoderError::KeySize(name.len()));
+        }
+        let (entry_len, max) = match self.sample_depth {
+            8 => (6, u16::from(u8::MAX)),
+            16 => (10, u16::MAX),
+            _ => {
+                return Err(EncoderError::ChunkValue(consts::SUGGESTED_PALETTE))
+            }
+        };
+        let fits = self.entries.iter().all(|entry| {
+            [entry.red, entry.green, entry.blue, entry.alpha]
+                .iter()
+                .all(|sample| *sample <= max)
+        });
+        if !fits {
+            return Err(EncoderError::ChunkValue(consts::SUGGESTED_PALETTE));
+        }
+
+        // Null-terminated name, sample depth, then the entries
+        enc.prepare(
+            name.len() + 2 + self.entries.len() * entry_len,
+            consts::SUGGESTED_PALETTE,
+        )?;
+        enc.raw(&name)?;
+        enc.u8(0)?;
+        enc.u8(self.sample_depth)?;
+        for entry in self.entries.iter() {
+            for sample in [entry.red, entry.green, entry.blue, entry.alpha] {
+                if self.sample_depth == 8 {
+                    enc.u8(sample as u8)?;
+                } else {
+                    enc.u16(sample)?;
+                }
+            }
+            enc.u16(entry.frequency)?;
+        }
+        enc.write_crc()
+    }
+}
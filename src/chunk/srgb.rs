@@ -0,0 +1,58 @@
+use std::io::{Read, Write};
+
+use super::{Chunk, DecoderError, EncoderError};
+use crate::{consts, decoder::Parser, encoder::Enc};
+
+/// Rendering intent of an sRGB image
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RenderingIntent {
+    /// For images preferring good adaptation to the output device gamut at
+    /// the expense of colorimetric accuracy, such as photographs
+    Perceptual = 0,
+    /// For images requiring color appearance matching (relative to the
+    /// output device white point), such as logos
+    RelativeColorimetric = 1,
+    /// For images preferring preservation of saturation at the expense of
+    /// hue and lightness, such as charts and graphs
+    Saturation = 2,
+    /// For images requiring preservation of absolute colorimetry, such as
+    /// previews of images destined for a different output device (proofs)
+    AbsoluteColorimetric = 3,
+}
+
+/// Standard RGB color space chunk (sRGB)
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SRgb {
+    /// Rendering intent
+    pub intent: RenderingIntent,
+}
+
+impl SRgb {
+    pub(crate) fn write<W: Write>(
+        &self,
+        enc: &mut Enc<W>,
+    ) -> Result<(), EncoderError> {
+        // 1 Byte
+        enc.prepare(1, consts::SRGB)?;
+        enc.u8(self.intent as u8)?;
+        enc.write_crc()
+    }
+
+    pub(crate) fn parse<R: Read>(
+        parse: &mut Parser<R>,
+    ) -> Result<Chunk, DecoderError> {
+        // 1 Byte
+        if parse.len() != 1 {
+            return Err(DecoderError::ChunkLength(consts::SRGB));
+        }
+        use RenderingIntent::*;
+        let intent = match parse.u8()? {
+            0 => Perceptual,
+            1 => RelativeColorimetric,
+            2 => Saturation,
+            3 => AbsoluteColorimetric,
+            intent => return Err(DecoderError::RenderingIntent(intent)),
+        };
+        Ok(Chunk::SRgb(SRgb { intent }))
+    }
+}
@@ -0,0 +1,43 @@
+use std::io::{Read, Write};
+
+use super::{Chunk, DecoderError, EncoderError};
+use crate::{consts, decoder::Parser, encoder::Enc};
+
+/// Stereo image chunk (sTER)
+///
+/// The image is a left-eye and right-eye image side by side, separated by a
+/// gap of `15 - (width - 1) % 16` columns so each starts on a multiple of 16.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Stereo {
+    /// Cross-fuse layout: the right-eye image is on the left
+    CrossFuse = 0,
+    /// Diverging-fuse layout: the left-eye image is on the left
+    DivergingFuse = 1,
+}
+
+impl Stereo {
+    pub(crate) fn write<W: Write>(
+        &self,
+        enc: &mut Enc<W>,
+    ) -> Result<(), EncoderError> {
+        // 1 Byte
+        enc.prepare(1, consts::STEREO)?;
+        enc.u8(*self as u8)?;
+        enc.write_crc()
+    }
+
+    pub(crate) fn parse<R: Read>(
+        parse: &mut Parser<R>,
+    ) -> Result<Chunk, DecoderError> {
+        // 1 Byte
+        if parse.len() != 1 {
+            return Err(DecoderError::ChunkLength(consts::STEREO));
+        }
+        let stereo = match parse.u8()? {
+            0 => Stereo::CrossFuse,
+            1 => Stereo::DivergingFuse,
+            _ => return Err(DecoderError::ChunkValue(consts::STEREO)),
+        };
+        Ok(Chunk::Stereo(stereo))
+    }
+}
@@ -3,7 +3,10 @@ use std::io::{Read, Write};
 use super::{Chunk, DecoderError, EncoderError};
 use crate::{consts, decoder::Parser, encoder::Enc};
 
-/// Non-International Text Chunk Data (tEXt and zTXt)
+/// Non-International Text Chunk Data (tEXt)
+///
+/// Both the keyword and text are ISO 8859-1 (Latin-1), so may only contain
+/// characters up to U+00FF.
 #[derive(Clone, Debug)]
 pub struct Text {
     /// A keyword that gives a short description of what the text in `val`
@@ -19,11 +22,12 @@ impl Text {
     pub(crate) fn parse<R: Read>(
         parse: &mut Parser<R>,
     ) -> Result<Chunk, DecoderError> {
-        let key = parse.str()?;
-        if key.is_empty() || key.len() > 79 {
-            return Err(DecoderError::KeySize(key.len()));
+        let key = parse.latin1_str()?;
+        let key_len = key.chars().count();
+        if key_len == 0 || key_len > 79 {
+            return Err(DecoderError::KeySize(key_len));
         }
-        let val = parse.string(parse.len() - (key.len() + 1))?;
+        let val = parse.latin1(parse.len() - (key_len + 1))?;
 
         Ok(Chunk::Text(Text { key, val }))
     }
@@ -33,14 +37,25 @@ impl Text {
         enc: &mut Enc<W>,
     ) -> Result<(), EncoderError> {
         // Checks
-        if self.key.as_bytes().is_empty() {
-            return Err(EncoderError::KeySize(0));
+        let key = latin1(&self.key)?;
+        if key.is_empty() || key.len() > 79 {
+            return Err(EncoderError::KeySize(key.len()));
         }
+        let val = latin1(&self.val)?;
 
         // 1 Null-terminated string, 1 string
-        enc.prepare(self.key.len() + self.val.len() + 1, consts::TEXT)?;
-        enc.str(&self.key)?;
-        enc.string(&self.val)?;
+        enc.prepare(key.len() + val.len() + 1, consts::TEXT)?;
+        enc.raw(&key)?;
+        enc.u8(0)?;
+        enc.raw(&val)?;
         enc.write_crc()
     }
 }
+
+/// Encode a string as ISO 8859-1 (Latin-1).
+pub(super) fn latin1(value: &str) -> Result<Vec<u8>, EncoderError> {
+    value
+        .chars()
+        .map(|c| u8::try_from(c).map_err(|_| EncoderError::Latin1))
+        .collect()
+}
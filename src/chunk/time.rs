@@ -4,7 +4,7 @@ use super::{Chunk, DecoderError, EncoderError};
 use crate::{consts, decoder::Parser, encoder::Enc};
 
 /// Time chunk (tIME)
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[allow(missing_docs)] // self-explanatory
 pub struct Time {
     pub year: u16,
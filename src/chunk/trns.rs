@@ -1,6 +1,6 @@
 use std::io::{Read, Write};
 
-use super::{Chunk, DecoderError, DecoderResult, EncoderResult};
+use super::{Chunk, ColorType, DecoderError, DecoderResult, EncoderResult};
 use crate::{consts, decoder::Parser, encoder::Enc};
 
 /// Alpha Palette Chunk Data (tRNS)
@@ -34,7 +34,11 @@ impl Transparency {
     pub(crate) fn parse<R: Read>(
         parse: &mut Parser<R>,
     ) -> DecoderResult<Chunk> {
-        if parse.has_palette() {
+        let palette = match parse.header() {
+            Some(header) => header.color_type == ColorType::Palette,
+            None => parse.has_palette(),
+        };
+        if palette {
             // Palette
             let apal = parse.raw()?;
             Ok(Chunk::Transparency(Transparency::Palette(apal)))
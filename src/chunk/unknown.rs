@@ -13,6 +13,27 @@ pub struct Unknown {
 }
 
 impl Unknown {
+    /// Check if the chunk is critical (uppercase first letter), rather than
+    /// ancillary.  Decoders can't display an image with an unknown critical
+    /// chunk, but may ignore unknown ancillary chunks.
+    pub fn is_critical(&self) -> bool {
+        self.name[0] & 0x20 == 0
+    }
+
+    /// Check if the chunk is public (uppercase second letter), rather than
+    /// private to an application.
+    pub fn is_public(&self) -> bool {
+        self.name[1] & 0x20 == 0
+    }
+
+    /// Check if the chunk is safe to copy (lowercase fourth letter) to a
+    /// modified file by an encoder that doesn't recognize it.  Chunks that
+    /// aren't safe to copy depend on the image data or critical chunks, so
+    /// may only be copied if those are unchanged.
+    pub fn is_safe_to_copy(&self) -> bool {
+        self.name[3] & 0x20 != 0
+    }
+
     pub(crate) fn write<W: Write>(
         &self,
         enc: &mut Enc<W>,
@@ -1,9 +1,15 @@
 use std::io::{Read, Write};
 
-use super::{Chunk, DecoderError, DecoderResult, EncoderError, EncoderResult};
+use super::{
+    text::latin1, Chunk, DecoderError, DecoderResult, EncoderError,
+    EncoderResult,
+};
 use crate::{consts, decoder::Parser, encoder::Enc, zlib};
 
 /// Compressed Text Chunk Data (zTXt)
+///
+/// Both the keyword and text are ISO 8859-1 (Latin-1), so may only contain
+/// characters up to U+00FF.
 #[derive(Clone, Debug)]
 pub struct CompressedText {
     /// A keyword that gives a short description of what the text in `val`
@@ -21,17 +27,19 @@ impl CompressedText {
         enc: &mut Enc<W>,
     ) -> EncoderResult<()> {
         // Checks
-        if self.key.as_bytes().is_empty() || self.key.as_bytes().len() > 79 {
-            return Err(EncoderError::KeySize(self.key.len()));
+        let key = latin1(&self.key)?;
+        if key.is_empty() || key.len() > 79 {
+            return Err(EncoderError::KeySize(key.len()));
         }
 
         // Compress text
         let mut zdata = Vec::new();
-        zlib::compress(&mut zdata, self.val.as_bytes(), enc.level());
+        zlib::compress(&mut zdata, &latin1(&self.val)?, enc.level());
 
         // Encode Chunk
-        enc.prepare(self.key.len() + 2 + zdata.len(), consts::ZTEXT)?;
-        enc.str(&self.key)?;
+        enc.prepare(key.len() + 2 + zdata.len(), consts::ZTEXT)?;
+        enc.raw(&key)?;
+        enc.u8(0)?;
         enc.u8(0)?; // Compression Method
         enc.raw(&zdata)?;
         enc.write_crc()
@@ -40,16 +48,17 @@ impl CompressedText {
     pub(crate) fn parse<R: Read>(
         parse: &mut Parser<R>,
     ) -> DecoderResult<Chunk> {
-        let key = parse.str()?;
+        let key = parse.latin1_str()?;
+        let key_len = key.chars().count();
         if parse.u8()? != 0 {
             return Err(DecoderError::CompressionMethod);
         }
-        let ztxt = parse.vec(parse.len() - (key.len() + 2))?;
+        let ztxt = parse.vec(parse.len() - (key_len + 2))?;
         let decoded = zlib::decompress(&ztxt)?;
-        if key.is_empty() || key.len() > 79 {
-            return Err(DecoderError::KeySize(key.len()));
+        if key_len == 0 || key_len > 79 {
+            return Err(DecoderError::KeySize(key_len));
         }
-        let val = String::from_utf8_lossy(&decoded).to_string();
+        let val = decoded.into_iter().map(char::from).collect();
 
         Ok(Chunk::CompressedText(CompressedText { key, val }))
     }
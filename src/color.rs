@@ -0,0 +1,342 @@
+//! Color space conversion to sRGB
+
+mod icc;
+
+use pix::rgb::{Rgb, SRgb8};
+
+use self::icc::Profile;
+use crate::{
+    chunk::Chromaticities, decode::Error as DecoderError, PngMetadata,
+    PngRaster,
+};
+
+/// sRGB primaries and D65 white point.
+const SRGB: Chromaticities = Chromaticities {
+    white: (0.3127, 0.3290),
+    red: (0.64, 0.33),
+    green: (0.30, 0.60),
+    blue: (0.15, 0.06),
+};
+
+/// Bradford cone response matrix for chromatic adaptation.
+const BRADFORD: [[f64; 3]; 3] = [
+    [0.8951, 0.2664, -0.1614],
+    [-0.7502, 1.7135, 0.0367],
+    [0.0389, -0.0685, 1.0296],
+];
+
+/// ICC profile connection space white point (D50), in XYZ.
+const D50: [f64; 3] = [0.9642, 1.0, 0.8249];
+
+/// 3x3 matrix, by rows.
+type Matrix = [[f64; 3]; 3];
+
+/// Transfer function from encoded values to linear light.
+#[derive(Clone, Debug)]
+pub(crate) enum Curve {
+    /// Power function with an exponent (the inverse of the gAMA value)
+    Gamma(f64),
+    /// The sRGB transfer function
+    Srgb,
+    /// Evenly spaced samples of the function, linearly interpolated
+    Table(Vec<f64>),
+    /// ICC parametric curve `[g, a, b, c, d, e, f]`: `(a * x + b) ^ g + e`
+    /// for `x >= d`, else `c * x + f`
+    Parametric([f64; 7]),
+}
+
+impl Curve {
+    /// Convert an encoded value (0 to 1) to linear light.
+    fn linear(&self, value: f64) -> f64 {
+        match *self {
+            Curve::Gamma(exponent) => value.powf(exponent),
+            Curve::Srgb => srgb_linear(value),
+            Curve::Table(ref table) => {
+                let position = value * (table.len() - 1) as f64;
+                let index = (position.floor() as usize).min(table.len() - 2);
+                let fraction = position - index as f64;
+                table[index] + (table[index + 1] - table[index]) * fraction
+            }
+            Curve::Parametric([g, a, b, c, d, e, f]) => {
+                if value >= d {
+                    (a * value + b).max(0.0).powf(g) + e
+                } else {
+                    c * value + f
+                }
+            }
+        }
+    }
+}
+
+/// Conversion of decoded pixels to sRGB.
+#[derive(Clone, Debug)]
+pub(crate) struct Transform {
+    /// Red, green and blue transfer functions (gray uses red)
+    curves: [Curve; 3],
+    /// Linear RGB to linear sRGB, `None` if the primaries are sRGB's
+    matrix: Option<Matrix>,
+}
+
+impl Transform {
+    /// Get the conversion for an image's color space metadata, `None` if the
+    /// pixels are already sRGB (or the color space is unknown).
+    ///
+    /// cICP takes precedence over everything (and isn't converted), then iCCP,
+    /// then sRGB, then gAMA and cHRM.  gAMA of 1/2.2 without cHRM is treated as
+    /// sRGB, and cHRM without gAMA is assumed to use the sRGB transfer
+    /// function.  Returns an error if the ICC profile is invalid,
+    /// unsupported or doesn't match `gray`.
+    pub(crate) fn new(
+        metadata: &PngMetadata,
+        gray: bool,
+    ) -> Result<Option<Self>, DecoderError> {
+        if metadata.code_points.is_some() {
+            return Ok(None);
+        }
+        if let Some(profile) = &metadata.color_profile {
+            return Self::with_profile(&profile.profile, gray).map(Some);
+        }
+        if metadata.srgb.is_some() {
+            return Ok(None);
+        }
+        let curve = match metadata.gamma {
+            Some(gamma) if gamma.gamma > 0.0 => Curve::Gamma(1.0 / gamma.gamma),
+            _ => Curve::Srgb,
+        };
+        let matrix = metadata.chromaticities.and_then(|chromaticities| {
+            to_srgb(&to_xyz(&chromaticities)?, xyz(chromaticities.white)?)
+        });
+        if matrix.is_none() {
+            match curve {
+                Curve::Srgb => return Ok(None),
+                Curve::Gamma(exponent) if (exponent - 2.2).abs() < 1e-3 => {
+                    return Ok(None)
+                }
+                _ => {}
+            }
+        }
+        Ok(Some(Self {
+            curves: [curve.clone(), curve.clone(), curve],
+            matrix,
+        }))
+    }
+
+    /// Get the conversion for an ICC profile.
+    fn with_profile(profile: &[u8], gray: bool) -> Result<Self, DecoderError> {
+        match (Profile::parse(profile)?, gray) {
+            (Profile::Gray(curve), true) => Ok(Self {
+                curves: [curve.clone(), curve.clone(), curve],
+                matrix: None,
+            }),
+            (Profile::Rgb(curves, matrix), false) => Ok(Self {
+                curves,
+                matrix: to_srgb(&matrix, D50),
+            }),
+            _ => Err(DecoderError::ColorProfile),
+        }
+    }
+
+    /// Convert a decoded raster to sRGB (alpha is unchanged).
+    pub(crate) fn apply(&self, raster: &mut PngRaster) {
+        use PngRaster::*;
+        match raster {
+            Gray8(r) => self.convert(r.as_u8_slice_mut(), 1, 1, false),
+            Gray16(r) => self.convert(r.as_u8_slice_mut(), 1, 1, true),
+            Graya8(r) => self.convert(r.as_u8_slice_mut(), 2, 1, false),
+            Graya16(r) => self.convert(r.as_u8_slice_mut(), 2, 1, true),
+            Rgb8(r) => self.convert(r.as_u8_slice_mut(), 3, 3, false),
+            Rgb16(r) => self.convert(r.as_u8_slice_mut(), 3, 3, true),
+            Rgba8(r) => self.convert(r.as_u8_slice_mut(), 4, 3, false),
+            Rgba16(r) => self.convert(r.as_u8_slice_mut(), 4, 3, true),
+            Palette(_, palette, _) => {
+                let mut colors: Vec<u8> = palette
+                    .colors()
+                    .iter()
+                    .flat_map(|color| {
+                        [
+                            Rgb::red(*color).into(),
+                            Rgb::green(*color).into(),
+                            Rgb::blue(*color).into(),
+                        ]
+                    })
+                    .collect();
+                self.convert(&mut colors, 3, 3, false);
+                for (i, color) in colors.chunks_exact(3).enumerate() {
+                    let color = SRgb8::new(color[0], color[1], color[2]);
+                    palette.replace_entry(i, color);
+                }
+            }
+        }
+    }
+
+    /// Convert samples with `channels` channels per pixel, of which the first
+    /// `colors` (1 for gray, 3 for RGB) are color channels.  16-bit samples
+    /// are big endian.
+    fn convert(
+        &self,
+        samples: &mut [u8],
+        channels: usize,
+        colors: usize,
+        wide: bool,
+    ) {
+        let bytes = if wide { 2 } else { 1 };
+        let max = if wide {
+            f64::from(u16::MAX)
+        } else {
+            f64::from(u8::MAX)
+        };
+        // Linear values of each 8-bit sample, per color channel
+        let tables: Vec<Vec<f64>> = if wide {
+            Vec::new()
+        } else {
+            self.curves
+                .iter()
+                .map(|curve| {
+                    (0..=u8::MAX)
+                        .map(|v| curve.linear(f64::from(v) / max))
+                        .collect()
+                })
+                .collect()
+        };
+
+        for pixel in samples.chunks_exact_mut(channels * bytes) {
+            let mut linear = [0.0; 3];
+            for (channel, value) in linear.iter_mut().enumerate().take(colors) {
+                *value = if wide {
+                    let sample = &pixel[channel * 2..][..2];
+                    let sample = u16::from_be_bytes([sample[0], sample[1]]);
+                    self.curves[channel].linear(f64::from(sample) / max)
+                } else {
+                    tables[channel][usize::from(pixel[channel])]
+                };
+            }
+            if let (Some(matrix), 3) = (&self.matrix, colors) {
+                linear = apply(matrix, linear);
+            }
+            for (channel, value) in linear.iter().enumerate().take(colors) {
+                let value = (srgb_encode(value.clamp(0.0, 1.0)) * max).round();
+                if wide {
+                    pixel[channel * 2..][..2]
+                        .copy_from_slice(&(value as u16).to_be_bytes());
+                } else {
+                    pixel[channel] = value as u8;
+                }
+            }
+        }
+    }
+}
+
+/// sRGB transfer function, from encoded to linear.
+fn srgb_linear(value: f64) -> f64 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Inverse sRGB transfer function, from linear to encoded.
+fn srgb_encode(value: f64) -> f64 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Get the XYZ of a chromaticity (with Y = 1).
+fn xyz((x, y): (f64, f64)) -> Option<[f64; 3]> {
+    (y > 0.0).then(|| [x / y, 1.0, (1.0 - x - y) / y])
+}
+
+/// Get the linear RGB to XYZ matrix of primaries and a white point.
+fn to_xyz(chromaticities: &Chromaticities) -> Option<Matrix> {
+    let [red, green, blue] = [
+        xyz(chromaticities.red)?,
+        xyz(chromaticities.green)?,
+        xyz(chromaticities.blue)?,
+    ];
+    let primaries = transpose(&[red, green, blue]);
+    let scale = apply(&invert(&primaries)?, xyz(chromaticities.white)?);
+    let mut matrix = primaries;
+    for row in matrix.iter_mut() {
+        for (value, scale) in row.iter_mut().zip(scale) {
+            *value *= scale;
+        }
+    }
+    Some(matrix)
+}
+
+/// Get the linear RGB to linear sRGB matrix from a linear RGB to XYZ matrix
+/// and its white point, `None` if it's (close to) identity.
+fn to_srgb(matrix: &Matrix, white: [f64; 3]) -> Option<Matrix> {
+    let matrix = multiply(
+        &invert(&to_xyz(&SRGB)?)?,
+        &multiply(&adapt(white, xyz(SRGB.white)?)?, matrix),
+    );
+    (!is_identity(&matrix)).then_some(matrix)
+}
+
+/// Get the Bradford chromatic adaptation matrix between white points (XYZ).
+fn adapt(from: [f64; 3], to: [f64; 3]) -> Option<Matrix> {
+    let from = apply(&BRADFORD, from);
+    let to = apply(&BRADFORD, to);
+    let mut scale = [[0.0; 3]; 3];
+    for i in 0..3 {
+        scale[i][i] = to[i] / from[i];
+    }
+    Some(multiply(&invert(&BRADFORD)?, &multiply(&scale, &BRADFORD)))
+}
+
+/// Multiply a matrix by a vector.
+fn apply(matrix: &Matrix, vector: [f64; 3]) -> [f64; 3] {
+    matrix
+        .map(|row| row[0] * vector[0] + row[1] * vector[1] + row[2] * vector[2])
+}
+
+/// Multiply two matrices.
+fn multiply(a: &Matrix, b: &Matrix) -> Matrix {
+    let b = transpose(b);
+    a.map(|row| apply(&b, row))
+}
+
+/// Transpose a matrix.
+fn transpose(matrix: &Matrix) -> Matrix {
+    let mut out = [[0.0; 3]; 3];
+    for (i, row) in matrix.iter().enumerate() {
+        for (j, value) in row.iter().enumerate() {
+            out[j][i] = *value;
+        }
+    }
+    out
+}
+
+/// Invert a matrix, `None` if it's singular.
+fn invert(m: &Matrix) -> Option<Matrix> {
+    let cofactor = |i: usize, j: usize| {
+        let (r0, r1) = ((i + 1) % 3, (i + 2) % 3);
+        let (c0, c1) = ((j + 1) % 3, (j + 2) % 3);
+        m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0]
+    };
+    let det = (0..3).map(|j| m[0][j] * cofactor(0, j)).sum::<f64>();
+    if det.abs() < f64::EPSILON {
+        return None;
+    }
+    let mut out = [[0.0; 3]; 3];
+    for (i, row) in out.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            *value = cofactor(j, i) / det;
+        }
+    }
+    Some(out)
+}
+
+/// Check if a matrix is close enough to identity to not change pixels.
+fn is_identity(matrix: &Matrix) -> bool {
+    matrix.iter().enumerate().all(|(i, row)| {
+        row.iter().enumerate().all(|(j, value)| {
+            let identity = if i == j { 1.0 } else { 0.0 };
+            (value - identity).abs() < 1e-4
+        })
+    })
+}
@@ -0,0 +1,146 @@
+//! ICC profile parsing (matrix/TRC and gray TRC profiles)
+
+use super::{Curve, Matrix};
+use crate::decode::Error as DecoderError;
+
+/// Signatures of the lookup table (LUT) based device to PCS transforms.
+const LUTS: [&[u8; 4]; 3] = [b"A2B0", b"A2B1", b"A2B2"];
+
+/// Device to profile connection space (XYZ) transform of an ICC profile.
+#[derive(Debug)]
+pub(super) enum Profile {
+    /// Gray tone reproduction curve (kTRC)
+    Gray(Curve),
+    /// Red, green and blue tone reproduction curves (rTRC, gTRC and bTRC),
+    /// and linear RGB to XYZ matrix (from rXYZ, gXYZ and bXYZ)
+    Rgb([Curve; 3], Matrix),
+}
+
+impl Profile {
+    /// Parse the header and tag table of an ICC profile.
+    pub(super) fn parse(data: &[u8]) -> Result<Self, DecoderError> {
+        let tags = tags(data).ok_or(DecoderError::ColorProfile)?;
+        let tag = |signature: &[u8; 4]| {
+            tags.iter()
+                .find(|(sig, _)| sig == signature)
+                .map(|(_, data)| *data)
+        };
+        // Data color space and profile connection space
+        let signatures: &[&[u8; 4]] = match (&data[16..20], &data[20..24]) {
+            (b"GRAY", b"XYZ ") => &[b"kTRC"],
+            (b"RGB ", b"XYZ ") => {
+                &[b"rTRC", b"gTRC", b"bTRC", b"rXYZ", b"gXYZ", b"bXYZ"]
+            }
+            _ => return Err(DecoderError::ColorProfile),
+        };
+        let Some(tags) = signatures
+            .iter()
+            .map(|signature| tag(signature))
+            .collect::<Option<Vec<_>>>()
+        else {
+            if LUTS.iter().any(|signature| tag(signature).is_some()) {
+                return Err(DecoderError::ProfileLut);
+            }
+            return Err(DecoderError::ColorProfile);
+        };
+        let profile = match *tags.as_slice() {
+            [trc] => curve(trc).map(Profile::Gray),
+            [r_trc, g_trc, b_trc, r_xyz, g_xyz, b_xyz] => {
+                matrix_trc([r_trc, g_trc, b_trc], [r_xyz, g_xyz, b_xyz])
+            }
+            _ => unreachable!(),
+        };
+        profile.ok_or(DecoderError::ColorProfile)
+    }
+}
+
+/// Parse the tone reproduction curves and colorant tags of a matrix/TRC
+/// profile.
+fn matrix_trc(trcs: [&[u8]; 3], xyzs: [&[u8]; 3]) -> Option<Profile> {
+    let [red, green, blue] = [xyz(xyzs[0])?, xyz(xyzs[1])?, xyz(xyzs[2])?];
+    let curves = [curve(trcs[0])?, curve(trcs[1])?, curve(trcs[2])?];
+    // Colorants are the columns of the matrix
+    let matrix = [0, 1, 2].map(|i| [red[i], green[i], blue[i]]);
+    Some(Profile::Rgb(curves, matrix))
+}
+
+/// Read a big endian `u32`.
+fn u32_at(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_be_bytes(bytes.try_into().unwrap()))
+}
+
+/// Read a big endian `u16`.
+fn u16_at(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset.checked_add(2)?)?;
+    Some(u16::from_be_bytes(bytes.try_into().unwrap()))
+}
+
+/// Read a big endian s15Fixed16Number.
+fn fixed_at(data: &[u8], offset: usize) -> Option<f64> {
+    Some(f64::from(u32_at(data, offset)? as i32) / 65536.0)
+}
+
+/// Get the signatures and data of the tags, `None` if the header or tag table
+/// is invalid.
+fn tags(data: &[u8]) -> Option<Vec<([u8; 4], &[u8])>> {
+    if data.get(36..40)? != b"acsp" {
+        return None;
+    }
+    let count = u32_at(data, 128)?;
+    (0..count)
+        .map(|i| {
+            let entry =
+                usize::try_from(i).ok()?.checked_mul(12)?.checked_add(132)?;
+            let signature = data.get(entry..entry + 4)?.try_into().unwrap();
+            let offset = usize::try_from(u32_at(data, entry + 4)?).ok()?;
+            let size = usize::try_from(u32_at(data, entry + 8)?).ok()?;
+            Some((signature, data.get(offset..offset.checked_add(size)?)?))
+        })
+        .collect()
+}
+
+/// Parse an XYZType tag.
+fn xyz(data: &[u8]) -> Option<[f64; 3]> {
+    if data.get(..4)? != b"XYZ " {
+        return None;
+    }
+    Some([fixed_at(data, 8)?, fixed_at(data, 12)?, fixed_at(data, 16)?])
+}
+
+/// Parse a curveType or parametricCurveType tag.
+fn curve(data: &[u8]) -> Option<Curve> {
+    match data.get(..4)? {
+        b"curv" => {
+            let count = usize::try_from(u32_at(data, 8)?).ok()?;
+            match count {
+                0 => Some(Curve::Gamma(1.0)),
+                1 => Some(Curve::Gamma(f64::from(u16_at(data, 12)?) / 256.0)),
+                _ => (0..count)
+                    .map(|i| {
+                        Some(f64::from(u16_at(data, 12 + i * 2)?) / 65535.0)
+                    })
+                    .collect::<Option<_>>()
+                    .map(Curve::Table),
+            }
+        }
+        b"para" => {
+            let function = u16_at(data, 8)?;
+            let count = [1, 3, 4, 5, 7].get(usize::from(function))?;
+            let params = (0..*count)
+                .map(|i| fixed_at(data, 12 + i * 4))
+                .collect::<Option<Vec<_>>>()?;
+            // Convert to the general form [g, a, b, c, d, e, f]
+            let params = match *params.as_slice() {
+                [g] => [g, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+                [g, a, b] if a != 0.0 => [g, a, b, 0.0, -b / a, 0.0, 0.0],
+                [g, a, b, c] if a != 0.0 => [g, a, b, 0.0, -b / a, c, c],
+                [g, a, b, c, d] => [g, a, b, c, d, 0.0, 0.0],
+                [g, a, b, c, d, e, f] => [g, a, b, c, d, e, f],
+                _ => return None,
+            };
+            Some(Curve::Parametric(params))
+        }
+        _ => None,
+    }
+}
@@ -13,6 +13,26 @@ pub(super) const PHYSICAL: [u8; 4] = *b"pHYs";
 pub(super) const TIME: [u8; 4] = *b"tIME";
 pub(super) const ZTEXT: [u8; 4] = *b"zTXt";
 pub(super) const TEXT: [u8; 4] = *b"tEXt";
+pub(super) const GAMMA: [u8; 4] = *b"gAMA";
+pub(super) const CHROMATICITIES: [u8; 4] = *b"cHRM";
+pub(super) const SRGB: [u8; 4] = *b"sRGB";
+pub(super) const COLOR_PROFILE: [u8; 4] = *b"iCCP";
+pub(super) const CODE_POINTS: [u8; 4] = *b"cICP";
+pub(super) const MASTERING_DISPLAY: [u8; 4] = *b"mDCv";
+pub(super) const LIGHT_LEVEL: [u8; 4] = *b"cLLi";
+pub(super) const EXIF: [u8; 4] = *b"eXIf";
+pub(super) const SIGNIFICANT_BITS: [u8; 4] = *b"sBIT";
+pub(super) const SUGGESTED_PALETTE: [u8; 4] = *b"sPLT";
+pub(super) const PALETTE_HISTOGRAM: [u8; 4] = *b"hIST";
+pub(super) const IMAGE_OFFSET: [u8; 4] = *b"oFFs";
+pub(super) const PIXEL_CALIBRATION: [u8; 4] = *b"pCAL";
+pub(super) const SUBJECT_PHYSICAL: [u8; 4] = *b"sCAL";
+pub(super) const STEREO: [u8; 4] = *b"sTER";
+pub(super) const GIF_GRAPHIC_CONTROL: [u8; 4] = *b"gIFg";
+pub(super) const GIF_APPLICATION: [u8; 4] = *b"gIFx";
+pub(super) const ANIMATION_CONTROL: [u8; 4] = *b"acTL";
+pub(super) const FRAME_CONTROL: [u8; 4] = *b"fcTL";
+pub(super) const FRAME_DATA: [u8; 4] = *b"fdAT";
 
 pub(super) const MAX_CHUNK_SIZE: usize = 1 << 31; // 2³¹
 
@@ -2,8 +2,10 @@
 
 mod chunks;
 mod error;
+mod frames;
 mod steps;
 
 pub use chunks::Chunks;
 pub use error::{Error, Result};
+pub use frames::Frames;
 pub use steps::Steps;
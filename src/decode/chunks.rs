@@ -2,9 +2,13 @@ use std::io::Read;
 
 use crate::{
     chunk::{
-        Background, Chunk, CompressedText, ImageData, ImageEnd, ImageHeader,
-        InternationalText, Palette, Physical, Text, Time, Transparency,
-        Unknown,
+        AnimationControl, Background, Chromaticities, Chunk, CodePoints,
+        ColorProfile, CompressedText, ContentLightLevel, Exif, FrameControl,
+        FrameData, Gamma, GifApplicationExt, GifGraphicControlExt, ImageData,
+        ImageEnd, ImageHeader, ImageOffset, InternationalText,
+        MasteringDisplay, Palette, PaletteHistogram, Physical,
+        PixelCalibration, SRgb, SignificantBits, Stereo, SubjectPhysical,
+        SuggestedPalette, Text, Time, Transparency, Unknown,
     },
     consts,
     decode::Result,
@@ -24,6 +28,11 @@ impl<R: Read> Chunks<R> {
         Chunks { dec }
     }
 
+    /// Get the chunk parser.
+    pub(crate) fn parser(&self) -> &Parser<R> {
+        &self.dec
+    }
+
     /// Get the next chunk in the PNG file.
     fn get_next(&mut self) -> Result<Option<Chunk>> {
         // Always start reading at the beginning of the next chunk:
@@ -46,7 +55,32 @@ impl<R: Read> Chunks<R> {
             TIME => Time::parse(&mut self.dec),
             TRANSPARENCY => Transparency::parse(&mut self.dec),
             ZTEXT => CompressedText::parse(&mut self.dec),
-            id => Unknown::parse(&mut self.dec, id),
+            GAMMA => Gamma::parse(&mut self.dec),
+            CHROMATICITIES => Chromaticities::parse(&mut self.dec),
+            SRGB => SRgb::parse(&mut self.dec),
+            COLOR_PROFILE => ColorProfile::parse(&mut self.dec),
+            CODE_POINTS => CodePoints::parse(&mut self.dec),
+            MASTERING_DISPLAY => MasteringDisplay::parse(&mut self.dec),
+            LIGHT_LEVEL => ContentLightLevel::parse(&mut self.dec),
+            EXIF => Exif::parse(&mut self.dec),
+            SIGNIFICANT_BITS => SignificantBits::parse(&mut self.dec),
+            SUGGESTED_PALETTE => SuggestedPalette::parse(&mut self.dec),
+            PALETTE_HISTOGRAM => PaletteHistogram::parse(&mut self.dec),
+            IMAGE_OFFSET => ImageOffset::parse(&mut self.dec),
+            PIXEL_CALIBRATION => PixelCalibration::parse(&mut self.dec),
+            SUBJECT_PHYSICAL => SubjectPhysical::parse(&mut self.dec),
+            STEREO => Stereo::parse(&mut self.dec),
+            GIF_GRAPHIC_CONTROL => GifGraphicControlExt::parse(&mut self.dec),
+            GIF_APPLICATION => GifApplicationExt::parse(&mut self.dec),
+            ANIMATION_CONTROL => AnimationControl::parse(&mut self.dec),
+            FRAME_CONTROL => FrameControl::parse(&mut self.dec),
+            FRAME_DATA => FrameData::parse(&mut self.dec),
+            id => match self.dec.custom(id) {
+                Some(codec) => {
+                    (codec.parse)(&self.dec.raw()?).map(Chunk::Custom)
+                }
+                None => Unknown::parse(&mut self.dec, id),
+            },
         }?;
         // Check the CRC Checksum at the end of the chunk.
         self.dec.check_crc(&name)?;
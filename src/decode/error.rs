@@ -12,6 +12,7 @@ impl From<std::io::Error> for Error {
 /// Decoding Errors.
 #[derive(Clone, Debug)]
 #[allow(variant_size_differences)]
+#[non_exhaustive]
 pub enum Error {
     /// A wrapped I/O error.
     Io(std::sync::Arc<std::io::Error>),
@@ -80,6 +81,28 @@ pub enum Error {
     Multiple([u8; 4]),
     /// CRC32 Checksum failed for a chunk
     Crc32([u8; 4]),
+    /// Unrecognized APNG frame dispose operation
+    DisposeOp(u8),
+    /// Unrecognized APNG frame blend operation
+    BlendOp(u8),
+    /// APNG frame region is empty or doesn't fit within the image
+    FrameRegion,
+    /// APNG chunk sequence number is out of order
+    Sequence(u32),
+    /// Unrecognized sRGB rendering intent
+    RenderingIntent(u8),
+    /// Chunk field value is out of range.  Provides PNG chunk identifier of
+    /// the chunk.
+    ChunkValue([u8; 4]),
+    /// ICC profile is invalid, doesn't match the color type, or isn't a
+    /// matrix/TRC or gray TRC profile
+    ColorProfile,
+    /// ICC profile uses lookup tables (LUTs), which aren't supported
+    ProfileLut,
+    /// Palette image doesn't have a PLTE chunk before the image data
+    NoPalette,
+    /// Number of APNG frames in the file (provided) doesn't match acTL
+    FrameCount(u32),
 }
 
 impl std::fmt::Display for Error {
@@ -119,6 +142,16 @@ impl std::fmt::Display for Error {
             TrailingChunk => write!(f, "Trailing chunks were found after IEND, which is invalid"),
             Multiple(bytes) => write!(f, "Only one {} chunk allowed, but found multiple", String::from_utf8_lossy(bytes)),
             Crc32(bytes) => write!(f, "CRC32 Checksum failed for {} chunk", String::from_utf8_lossy(bytes)),
+            DisposeOp(op) => write!(f, "Unrecognized frame dispose operation {}", op),
+            BlendOp(op) => write!(f, "Unrecognized frame blend operation {}", op),
+            FrameRegion => write!(f, "Frame region is empty or doesn't fit within the image"),
+            Sequence(seq) => write!(f, "Sequence number {} is out of order", seq),
+            RenderingIntent(intent) => write!(f, "Unrecognized rendering intent {}", intent),
+            ChunkValue(bytes) => write!(f, "{} chunk value out of range", String::from_utf8_lossy(bytes)),
+            ColorProfile => write!(f, "Invalid or unsupported ICC profile"),
+            ProfileLut => write!(f, "LUT-based ICC profiles are not supported"),
+            NoPalette => write!(f, "Palette image has no PLTE chunk"),
+            FrameCount(frames) => write!(f, "Animation has {} frames, which doesn't match acTL", frames),
         }
     }
 }
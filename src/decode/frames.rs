@@ -0,0 +1,38 @@
+use std::io::Read;
+
+use crate::{
+    decode::{Chunks, Result, Steps},
+    Step,
+};
+
+/// Iterator over raw (uncomposited) APNG frames.
+///
+/// Each frame's raster is the frame region exactly as stored in the file
+/// (before disposal and blending), in the image's color type.  The region and
+/// operations are in the step's
+/// [`FrameControl`](../chunk/struct.FrameControl.html).  Non-animated PNG
+/// files have a single full-size frame without a `FrameControl`.
+#[derive(Debug)]
+pub struct Frames<R: Read> {
+    steps: Steps<R>,
+}
+
+impl<R: Read> Frames<R> {
+    /// Create a new decoder.
+    pub(crate) fn new(chunks: Chunks<R>) -> Self {
+        Self {
+            steps: Steps::new_raw(chunks),
+        }
+    }
+}
+
+impl<R> Iterator for Frames<R>
+where
+    R: Read,
+{
+    type Item = Result<Step>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.steps.next()
+    }
+}
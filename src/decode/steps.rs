@@ -1,308 +1,681 @@
-use std::{collections::HashMap, io::Read, iter::Peekable};
+use std::{io::Read, iter::Peekable};
 
-use pix::{Palette, Raster};
+use pix::{rgb::SRgb8, Palette, Raster};
 
 use crate::{
+    canvas::Canvas,
     chunk::{
-        Background, Chunk, ColorType, ImageHeader, Palette as PaletteChunk,
-        Physical, Time, Transparency,
+        AnimationControl, Background, Chunk, ChunkPosition, ColorType,
+        FrameControl, ImageHeader, Orientation, Palette as PaletteChunk,
+        Transparency,
     },
+    color::Transform,
     consts,
     decode::{Chunks, Error as DecoderError},
-    zlib, PngRaster, Step,
+    zlib, PngMetadata, PngRaster, Step, TextEntry,
 };
 
 mod unfilter;
 
-#[derive(Debug)]
-struct TextEntry {
-    #[allow(dead_code)] // FIXME
-    text: String,
-    #[allow(dead_code)] // FIXME
-    langtag: Option<String>,
-    #[allow(dead_code)] // FIXME
-    transkey: Option<String>,
-}
-
 /// Iterator over `Step`s for PNG files.
+///
+/// Animation frames of APNG files are composited onto a full-size canvas, and
+/// returned as `PngRaster::Rgba8` (or `PngRaster::Rgba16` for 16-bit images).
+/// If the number of frames doesn't match acTL, the last item is an
+/// `Error::FrameCount`.
+///
+/// Unknown ancillary chunks and custom chunks are collected into the metadata
+/// with their position.  Unknown critical chunks (which
+/// [`Chunks`](struct.Chunks.html) returns as `Chunk::Unknown`) are an
+/// `Error::UnknownChunkType`.
 #[derive(Debug)]
 pub struct Steps<R: Read> {
     decoder: Peekable<Chunks<R>>,
-    // FIXME: This is a workaround for not supporting APNG yet.
-    #[allow(dead_code)]
-    has_decoded: bool,
     // None if haven't decoded a frame yet.
     header: Option<ImageHeader>,
-    // Is the file an APNG animation?
-    #[allow(dead_code)]
-    is_animation: bool,
-    // Is IDAT part of the animation?
-    #[allow(dead_code)]
-    idat_anim: bool,
+    // Some if the file is an APNG animation.
+    animation: Option<AnimationControl>,
+    // Frame control for the next frame.
+    frame: Option<FrameControl>,
+    // Number of animation frames decoded so far.
+    frames: u32,
+    // Output buffer for APNG animations.
+    canvas: Option<Canvas>,
     //
     palette: Option<PaletteChunk>,
     //
     transparency: Option<Transparency>,
-    //
-    background: Option<Background>,
-    //
-    text: HashMap<String, TextEntry>,
-    //
-    physical: Option<Physical>,
-    //
-    time: Option<Time>,
+    // Ancillary metadata.
+    metadata: PngMetadata,
     // True if after palette chunk found
     reject_pal: bool,
+    // True if animation frames are returned without compositing.
+    raw: bool,
+    // True if tRNS color keys are kept as metadata.
+    keep_key: bool,
+    // True if pixels are converted to sRGB.
+    srgb: bool,
+    // Conversion to sRGB, if needed.
+    color: Option<Transform>,
+    // True if the EXIF orientation is applied.
+    orient: bool,
+    // EXIF orientation to apply, if any.
+    orientation: Option<Orientation>,
+    // True if samples are shifted to their significant bits.
+    shift: bool,
 }
 
 impl<R: Read> Steps<R> {
     /// Create a new decoder.
     pub(crate) fn new(chunks: Chunks<R>) -> Self {
+        let keep_key = chunks.parser().keep_key();
+        let srgb = chunks.parser().convert_srgb();
+        let orient = chunks.parser().orient();
+        let shift = chunks.parser().shift_significant_bits();
         let decoder = chunks.peekable();
 
         Self {
             decoder,
-            has_decoded: false,
             header: None,
-            idat_anim: false,
-            is_animation: false,
+            animation: None,
+            frame: None,
+            frames: 0,
+            canvas: None,
             palette: None,
             transparency: None,
-            background: None,
-            physical: None,
-            text: HashMap::new(),
-            time: None,
+            metadata: PngMetadata::default(),
             reject_pal: false,
+            raw: false,
+            keep_key,
+            srgb,
+            color: None,
+            orient,
+            orientation: None,
+            shift,
         }
     }
-}
 
-impl<R> Iterator for Steps<R>
-where
-    R: Read,
-{
-    type Item = Result<Step, DecoderError>;
+    /// Create a new decoder that doesn't composite animation frames.
+    pub(crate) fn new_raw(chunks: Chunks<R>) -> Self {
+        Self {
+            raw: true,
+            ..Self::new(chunks)
+        }
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        // First frame
-        if self.header.is_none() {
-            // First chunk must be IHDR
-            self.header = match self.decoder.next().ok_or(DecoderError::Empty) {
-                Ok(Ok(Chunk::ImageHeader(header))) => Some(header),
-                Ok(Ok(_chunk)) => return Some(Err(DecoderError::ChunkOrder)),
-                Ok(Err(e)) => return Some(Err(e)),
-                Err(e) => return Some(Err(e)),
-            };
+    /// Get the tRNS chunk, once the first step has been decoded.  Color keys
+    /// of gray and RGB images are only kept (rather than decoded as an alpha
+    /// channel) with
+    /// [`Decoder::keep_transparency_key`](crate::Decoder::keep_transparency_key).
+    pub fn transparency(&self) -> Option<&Transparency> {
+        self.transparency.as_ref()
+    }
 
-            // Go through chunks before IDAT
-            while {
-                match self.decoder.peek() {
-                    Some(Ok(chunk)) => !chunk.is_idat(),
-                    Some(Err(DecoderError::UnknownChunkType(_))) => true,
-                    Some(Err(e)) => return Some(Err(e.clone())),
-                    None => return Some(Err(DecoderError::NoImageData)),
-                }
-            } {
-                use Chunk::*;
-                // Won't panic
-                let chunk = if let Ok(chunk) = self.decoder.next().unwrap() {
-                    chunk
-                } else {
-                    continue; // Skip unknown chunks
-                };
-                match chunk {
-                    Palette(chunk) => {
-                        if self.reject_pal {
-                            return Some(Err(DecoderError::ChunkOrder));
-                        }
-                        if self.palette.is_some() {
-                            return Some(Err(DecoderError::Multiple(
-                                consts::PALETTE,
-                            )));
-                        }
-                        self.palette = Some(chunk)
-                    }
-                    Background(chunk) => {
-                        self.reject_pal = true;
-                        if self.background.is_some() {
-                            return Some(Err(DecoderError::Multiple(
-                                consts::BACKGROUND,
-                            )));
-                        }
-                        self.background = Some(chunk);
-                    }
-                    InternationalText(chunk) => {
-                        self.text.insert(
-                            chunk.key,
-                            TextEntry {
-                                text: chunk.val,
-                                langtag: Some(chunk.langtag),
-                                transkey: Some(chunk.transkey),
-                            },
-                        );
-                    }
-                    CompressedText(chunk) => {
-                        self.text.insert(
-                            chunk.key,
-                            TextEntry {
-                                text: chunk.val,
-                                langtag: None,
-                                transkey: None,
-                            },
-                        );
-                    }
-                    Text(chunk) => {
-                        self.text.insert(
-                            chunk.key,
-                            TextEntry {
-                                text: chunk.val,
-                                langtag: None,
-                                transkey: None,
-                            },
-                        );
-                    }
-                    Physical(chunk) => {
-                        if self.physical.is_some() {
-                            return Some(Err(DecoderError::Multiple(
-                                consts::PHYSICAL,
-                            )));
-                        }
-                        self.physical = Some(chunk);
+    /// Get the metadata (text, time, pHYs, bKGD, color space, HDR and EXIF).
+    /// Chunks before the image data are available once the first step has
+    /// been decoded, and chunks after it once the iterator has returned
+    /// `None`.
+    pub fn metadata(&self) -> &PngMetadata {
+        &self.metadata
+    }
+
+    /// Go through the chunks before the first IDAT chunk.
+    fn prelude(&mut self) -> Result<(), DecoderError> {
+        // First chunk must be IHDR
+        let header = match self.decoder.next().ok_or(DecoderError::Empty)? {
+            Ok(Chunk::ImageHeader(header)) => header,
+            Ok(_chunk) => return Err(DecoderError::ChunkOrder),
+            Err(e) => return Err(e),
+        };
+        self.header = Some(header);
+
+        // Go through chunks before IDAT
+        while {
+            match self.decoder.peek() {
+                Some(Ok(chunk)) => !chunk.is_idat(),
+                Some(Err(e)) => return Err(e.clone()),
+                None => return Err(DecoderError::NoImageData),
+            }
+        } {
+            use Chunk::*;
+            // Won't panic, errors were returned above
+            match self.decoder.next().unwrap()? {
+                Palette(chunk) => {
+                    if self.reject_pal {
+                        return Err(DecoderError::ChunkOrder);
                     }
-                    Time(chunk) => {
-                        if self.time.is_some() {
-                            return Some(Err(DecoderError::Multiple(
-                                consts::TIME,
-                            )));
-                        }
-                        self.time = Some(chunk);
-                    }
-                    Transparency(chunk) => {
-                        self.reject_pal = true;
-                        if self.transparency.is_some() {
-                            return Some(Err(DecoderError::Multiple(
-                                consts::TRANSPARENCY,
-                            )));
-                        }
-                        self.transparency = Some(chunk);
+                    if self.palette.is_some() {
+                        return Err(DecoderError::Multiple(consts::PALETTE));
                     }
-                    ImageHeader(_) => {
-                        return Some(Err(DecoderError::ChunkOrder))
+                    self.palette = Some(chunk)
+                }
+                Background(chunk) => {
+                    self.reject_pal = true;
+                    if self.metadata.background.is_some() {
+                        return Err(DecoderError::Multiple(consts::BACKGROUND));
                     }
-                    ImageEnd(_) => return Some(Err(DecoderError::NoImageData)),
-                    ImageData(_) => unreachable!(),
-                    Unknown(_) => continue, // Skip unknown chunks
+                    self.metadata.background = Some(chunk);
                 }
+                Physical(chunk) => {
+                    if self.metadata.physical.is_some() {
+                        return Err(DecoderError::Multiple(consts::PHYSICAL));
+                    }
+                    self.metadata.physical = Some(chunk);
+                }
+                Transparency(chunk) => {
+                    self.reject_pal = true;
+                    if self.transparency.is_some() {
+                        return Err(DecoderError::Multiple(
+                            consts::TRANSPARENCY,
+                        ));
+                    }
+                    self.transparency = Some(chunk);
+                }
+                Gamma(chunk) => {
+                    if self.palette.is_some() {
+                        return Err(DecoderError::ChunkOrder);
+                    }
+                    if self.metadata.gamma.is_some() {
+                        return Err(DecoderError::Multiple(consts::GAMMA));
+                    }
+                    self.metadata.gamma = Some(chunk);
+                }
+                Chromaticities(chunk) => {
+                    if self.palette.is_some() {
+                        return Err(DecoderError::ChunkOrder);
+                    }
+                    if self.metadata.chromaticities.is_some() {
+                        return Err(DecoderError::Multiple(
+                            consts::CHROMATICITIES,
+                        ));
+                    }
+                    self.metadata.chromaticities = Some(chunk);
+                }
+                SRgb(chunk) => {
+                    if self.palette.is_some() {
+                        return Err(DecoderError::ChunkOrder);
+                    }
+                    if self.metadata.srgb.is_some() {
+                        return Err(DecoderError::Multiple(consts::SRGB));
+                    }
+                    self.metadata.srgb = Some(chunk);
+                }
+                ColorProfile(chunk) => {
+                    if self.palette.is_some() {
+                        return Err(DecoderError::ChunkOrder);
+                    }
+                    if self.metadata.color_profile.is_some() {
+                        return Err(DecoderError::Multiple(
+                            consts::COLOR_PROFILE,
+                        ));
+                    }
+                    self.metadata.color_profile = Some(chunk);
+                }
+                SignificantBits(chunk) => {
+                    if self.palette.is_some() {
+                        return Err(DecoderError::ChunkOrder);
+                    }
+                    if self.metadata.significant_bits.is_some() {
+                        return Err(DecoderError::Multiple(
+                            consts::SIGNIFICANT_BITS,
+                        ));
+                    }
+                    self.metadata.significant_bits = Some(chunk);
+                }
+                SuggestedPalette(chunk) => {
+                    let palettes = &self.metadata.suggested_palettes;
+                    if palettes.iter().any(|p| p.name == chunk.name) {
+                        return Err(DecoderError::ChunkValue(
+                            consts::SUGGESTED_PALETTE,
+                        ));
+                    }
+                    self.metadata.suggested_palettes.push(chunk);
+                }
+                PaletteHistogram(chunk) => {
+                    let Some(palette) = &self.palette else {
+                        return Err(DecoderError::ChunkOrder);
+                    };
+                    if self.metadata.histogram.is_some() {
+                        return Err(DecoderError::Multiple(
+                            consts::PALETTE_HISTOGRAM,
+                        ));
+                    }
+                    if chunk.frequencies.len() != palette.palette.len() {
+                        return Err(DecoderError::ChunkLength(
+                            consts::PALETTE_HISTOGRAM,
+                        ));
+                    }
+                    self.metadata.histogram = Some(chunk);
+                }
+                ImageOffset(chunk) => {
+                    if self.metadata.offset.is_some() {
+                        return Err(DecoderError::Multiple(
+                            consts::IMAGE_OFFSET,
+                        ));
+                    }
+                    self.metadata.offset = Some(chunk);
+                }
+                PixelCalibration(chunk) => {
+                    if self.metadata.calibration.is_some() {
+                        return Err(DecoderError::Multiple(
+                            consts::PIXEL_CALIBRATION,
+                        ));
+                    }
+                    self.metadata.calibration = Some(chunk);
+                }
+                SubjectPhysical(chunk) => {
+                    if self.metadata.subject_physical.is_some() {
+                        return Err(DecoderError::Multiple(
+                            consts::SUBJECT_PHYSICAL,
+                        ));
+                    }
+                    self.metadata.subject_physical = Some(chunk);
+                }
+                Stereo(chunk) => {
+                    if self.metadata.stereo.is_some() {
+                        return Err(DecoderError::Multiple(consts::STEREO));
+                    }
+                    self.metadata.stereo = Some(chunk);
+                }
+                CodePoints(chunk) => {
+                    if self.palette.is_some() {
+                        return Err(DecoderError::ChunkOrder);
+                    }
+                    if self.metadata.code_points.is_some() {
+                        return Err(DecoderError::Multiple(
+                            consts::CODE_POINTS,
+                        ));
+                    }
+                    self.metadata.code_points = Some(chunk);
+                }
+                MasteringDisplay(chunk) => {
+                    if self.metadata.mastering_display.is_some() {
+                        return Err(DecoderError::Multiple(
+                            consts::MASTERING_DISPLAY,
+                        ));
+                    }
+                    self.metadata.mastering_display = Some(chunk);
+                }
+                ContentLightLevel(chunk) => {
+                    if self.metadata.light_level.is_some() {
+                        return Err(DecoderError::Multiple(
+                            consts::LIGHT_LEVEL,
+                        ));
+                    }
+                    self.metadata.light_level = Some(chunk);
+                }
+                AnimationControl(chunk) => {
+                    if self.animation.is_some() {
+                        return Err(DecoderError::Multiple(
+                            consts::ANIMATION_CONTROL,
+                        ));
+                    }
+                    self.animation = Some(chunk);
+                }
+                FrameControl(chunk) => {
+                    if self.frame.is_some() {
+                        return Err(DecoderError::Multiple(
+                            consts::FRAME_CONTROL,
+                        ));
+                    }
+                    self.frame = Some(chunk);
+                }
+                ImageHeader(_) | FrameData(_) => {
+                    return Err(DecoderError::ChunkOrder)
+                }
+                ImageEnd(_) => return Err(DecoderError::NoImageData),
+                ImageData(_) => unreachable!(),
+                Custom(custom) => {
+                    let position = match self.palette {
+                        Some(_) => ChunkPosition::BeforeImageData,
+                        None => ChunkPosition::BeforePalette,
+                    };
+                    if position > custom.position() {
+                        return Err(DecoderError::ChunkOrder);
+                    }
+                    self.metadata.custom_chunks.push((position, custom));
+                }
+                Unknown(unknown) if unknown.is_critical() => {
+                    return Err(DecoderError::UnknownChunkType(unknown.name))
+                }
+                Unknown(unknown) => {
+                    let position = match self.palette {
+                        Some(_) => ChunkPosition::BeforeImageData,
+                        None => ChunkPosition::BeforePalette,
+                    };
+                    self.metadata.unknown_chunks.push((position, unknown));
+                }
+                chunk => self.ancillary(chunk)?,
             }
         }
+        if header.color_type == ColorType::Palette && self.palette.is_none() {
+            return Err(DecoderError::NoPalette);
+        }
 
-        // Check for ImageEnd
-        if let Some(Ok(chunk)) = self.decoder.peek() {
-            if chunk.is_iend() {
-                if let Err(e) = self.decoder.next().unwrap() {
-                    return Some(Err(e));
+        // Scale gray backgrounds up to 8 bits, like the raster
+        if let Some(Background::Gray(gray)) = self.metadata.background.as_mut()
+        {
+            if header.color_type == ColorType::Grey && header.bit_depth < 8 {
+                *gray *=
+                    u16::from(u8::MAX / (u8::MAX >> (8 - header.bit_depth)));
+            }
+        }
+
+        if let Some(significant_bits) = self.metadata.significant_bits {
+            if !significant_bits.is_valid(header.color_type, header.bit_depth) {
+                return Err(DecoderError::ChunkValue(consts::SIGNIFICANT_BITS));
+            }
+        }
+
+        if self.srgb {
+            let gray = matches!(
+                header.color_type,
+                ColorType::Grey | ColorType::GreyAlpha
+            );
+            self.color = Transform::new(&self.metadata, gray)?;
+        }
+        if self.orient {
+            self.orientation = self
+                .metadata
+                .exif
+                .as_ref()
+                .and_then(|exif| exif.orientation());
+        }
+
+        // Frame control before IDAT must cover the whole image
+        if let Some(frame) = self.frame {
+            if !frame.is_full(&header) {
+                return Err(DecoderError::FrameRegion);
+            }
+        }
+
+        // Set up the output buffer for animations
+        if self.animation.is_some() {
+            self.canvas = Some(Canvas::new(
+                header.width,
+                header.height,
+                header.bit_depth == 16,
+            ));
+        } else {
+            // Frame control chunks are ignored in non-animated images.
+            self.frame = None;
+        }
+
+        Ok(())
+    }
+
+    /// Handle a chunk allowed both before and after image data.
+    fn ancillary(&mut self, chunk: Chunk) -> Result<(), DecoderError> {
+        use Chunk::*;
+        match chunk {
+            InternationalText(chunk) => {
+                self.metadata.text.push(TextEntry {
+                    key: chunk.key,
+                    text: chunk.val,
+                    langtag: Some(chunk.langtag),
+                    transkey: Some(chunk.transkey),
+                    compressed: chunk.compressed,
+                });
+            }
+            CompressedText(chunk) => {
+                self.metadata.text.push(TextEntry {
+                    key: chunk.key,
+                    text: chunk.val,
+                    langtag: None,
+                    transkey: None,
+                    compressed: true,
+                });
+            }
+            Text(chunk) => {
+                self.metadata.text.push(TextEntry {
+                    key: chunk.key,
+                    text: chunk.val,
+                    langtag: None,
+                    transkey: None,
+                    compressed: false,
+                });
+            }
+            Time(chunk) => {
+                if self.metadata.time.is_some() {
+                    return Err(DecoderError::Multiple(consts::TIME));
                 }
-                if self.decoder.next().is_some() {
-                    return Some(Err(DecoderError::TrailingChunk));
+                self.metadata.time = Some(chunk);
+            }
+            Exif(chunk) => {
+                if self.metadata.exif.is_some() {
+                    return Err(DecoderError::Multiple(consts::EXIF));
                 }
-                return None;
+                self.metadata.exif = Some(chunk);
+            }
+            GifGraphicControlExt(chunk) => {
+                self.metadata.gif_graphic_controls.push(chunk);
+            }
+            GifApplicationExt(chunk) => {
+                self.metadata.gif_applications.push(chunk);
+            }
+            // Ignored without acTL, like other unknown ancillary chunks
+            FrameControl(_) if self.animation.is_none() => {}
+            FrameControl(chunk) => {
+                if self.frame.is_some() {
+                    return Err(DecoderError::ChunkOrder);
+                }
+                self.frame = Some(chunk);
+            }
+            ImageHeader(_) => return Err(DecoderError::ChunkOrder),
+            Palette(_) => return Err(DecoderError::ChunkOrder),
+            Background(_) => return Err(DecoderError::ChunkOrder),
+            Physical(_) => return Err(DecoderError::ChunkOrder),
+            Transparency(_) => return Err(DecoderError::ChunkOrder),
+            AnimationControl(_) => return Err(DecoderError::ChunkOrder),
+            Gamma(_) | Chromaticities(_) | SRgb(_) | ColorProfile(_) => {
+                return Err(DecoderError::ChunkOrder)
+            }
+            CodePoints(_) | MasteringDisplay(_) | ContentLightLevel(_) => {
+                return Err(DecoderError::ChunkOrder)
+            }
+            SignificantBits(_) => return Err(DecoderError::ChunkOrder),
+            SuggestedPalette(_) | PaletteHistogram(_) => {
+                return Err(DecoderError::ChunkOrder)
+            }
+            ImageOffset(_) | PixelCalibration(_) | SubjectPhysical(_)
+            | Stereo(_) => return Err(DecoderError::ChunkOrder),
+            ImageData(_) => unreachable!(),
+            FrameData(_) => unreachable!(),
+            ImageEnd(_) => unreachable!(),
+            Custom(custom) => {
+                if custom.position() != ChunkPosition::AfterImageData {
+                    return Err(DecoderError::ChunkOrder);
+                }
+                self.metadata
+                    .custom_chunks
+                    .push((ChunkPosition::AfterImageData, custom));
+            }
+            Unknown(unknown) if unknown.is_critical() => {
+                return Err(DecoderError::UnknownChunkType(unknown.name))
+            }
+            Unknown(unknown) => {
+                self.metadata
+                    .unknown_chunks
+                    .push((ChunkPosition::AfterImageData, unknown));
             }
         }
+        Ok(())
+    }
 
-        // Image data for consecutive IDAT chunks.
-        let mut idat = Vec::new();
-
-        // Go through until the last IDAT or fdAT chunk.
-        while {
-            let chunk = match self.decoder.peek() {
-                Some(Ok(chunk)) => chunk,
-                Some(Err(e)) => return Some(Err(e.clone())),
-                None => return Some(Err(DecoderError::NoImageData)),
-            };
-            chunk.is_idat()
-        } {
-            match self.decoder.next().unwrap() {
-                Ok(Chunk::ImageData(data)) => idat.extend(data.data),
-                Ok(_) => unreachable!(),
-                Err(e) => return Some(Err(e)),
+    /// Get the compressed image data of consecutive IDAT or fdAT chunks.
+    fn image_data(&mut self) -> Result<Vec<u8>, DecoderError> {
+        let mut data = Vec::new();
+        loop {
+            match self.decoder.peek() {
+                Some(Ok(Chunk::ImageData(_))) => {
+                    if let Some(Ok(Chunk::ImageData(chunk))) =
+                        self.decoder.next()
+                    {
+                        data.extend(chunk.data);
+                    }
+                }
+                Some(Ok(Chunk::FrameData(_))) => {
+                    if let Some(Ok(Chunk::FrameData(chunk))) =
+                        self.decoder.next()
+                    {
+                        data.extend(chunk.data);
+                    }
+                }
+                Some(Err(e)) => return Err(e.clone()),
+                _ => return Ok(data),
             }
         }
+    }
 
-        let raster = match decode(
-            idat.as_slice(),
-            self.header.as_ref().unwrap(),
-            self.palette.as_ref(),
-            self.transparency.as_ref(),
-        ) {
-            Ok(raster) => raster,
-            Err(e) => return Some(Err(e)),
+    /// Shift samples right to their significant bits, if enabled.
+    fn shift(&self, raster: &mut PngRaster) {
+        let Some(significant_bits) = self.metadata.significant_bits else {
+            return;
         };
+        if !self.shift {
+            return;
+        }
+        raster.map_samples(|channel, value, bits| {
+            match significant_bits.bits(channel) {
+                Some(significant) => value >> (bits - significant.min(bits)),
+                None => value,
+            }
+        });
+    }
 
-        // Check for non-required chunks up until the next IDAT or fdAT chunk or
-        // end
-        while {
+    /// Decode the next `Step`, `None` if the end of the file was reached.
+    fn get_next(&mut self) -> Result<Option<Step>, DecoderError> {
+        // First frame
+        if self.header.is_none() {
+            self.prelude()?;
+        }
+        let header = self.header.unwrap();
+
+        loop {
             let chunk = match self.decoder.peek() {
                 Some(Ok(chunk)) => chunk,
-                Some(Err(e)) => return Some(Err(e.clone())),
-                None => return Some(Err(DecoderError::NoImageData)),
+                Some(Err(e)) => return Err(e.clone()),
+                None => return Err(DecoderError::NoImageData),
             };
-            !chunk.is_idat() && !chunk.is_iend()
-        } {
-            use Chunk::*;
-            match self.decoder.next().unwrap().unwrap() {
-                // won't panic
-                InternationalText(chunk) => {
-                    self.text.insert(
-                        chunk.key,
-                        TextEntry {
-                            text: chunk.val,
-                            langtag: Some(chunk.langtag),
-                            transkey: Some(chunk.transkey),
-                        },
-                    );
-                }
-                CompressedText(chunk) => {
-                    self.text.insert(
-                        chunk.key,
-                        TextEntry {
-                            text: chunk.val,
-                            langtag: None,
-                            transkey: None,
-                        },
-                    );
-                }
-                Text(chunk) => {
-                    self.text.insert(
-                        chunk.key,
-                        TextEntry {
-                            text: chunk.val,
-                            langtag: None,
-                            transkey: None,
-                        },
-                    );
-                }
-                Time(chunk) => {
-                    if self.time.is_some() {
-                        return Some(Err(DecoderError::Multiple(consts::TIME)));
-                    }
-                    self.time = Some(chunk);
-                }
-                ImageHeader(_) => return Some(Err(DecoderError::ChunkOrder)),
-                Palette(_) => return Some(Err(DecoderError::ChunkOrder)),
-                Background(_) => return Some(Err(DecoderError::ChunkOrder)),
-                Physical(_) => return Some(Err(DecoderError::ChunkOrder)),
-                Transparency(_) => return Some(Err(DecoderError::ChunkOrder)),
-                ImageData(_) => unreachable!(),
-                ImageEnd(_) => unreachable!(),
-                Unknown(unknown) => {
-                    return Some(Err(DecoderError::UnknownChunkType(
-                        unknown.name,
-                    )))
+
+            // Check for ImageEnd
+            if chunk.is_iend() {
+                if let Some(animation) = self.animation {
+                    if self.frames != animation.num_frames {
+                        return Err(DecoderError::FrameCount(self.frames));
+                    }
+                }
+                self.decoder.next().unwrap()?;
+                if self.decoder.next().is_some() {
+                    return Err(DecoderError::TrailingChunk);
                 }
+                return Ok(None);
             }
+
+            // Image data for consecutive IDAT or fdAT chunks.
+            if chunk.is_idat() || chunk.is_fdat() {
+                let is_idat = chunk.is_idat();
+                let frame = self.frame.take();
+                let data = self.image_data()?;
+                let transparency = match self.transparency {
+                    Some(Transparency::Palette(_)) => {
+                        self.transparency.as_ref()
+                    }
+                    _ if self.keep_key => None,
+                    _ => self.transparency.as_ref(),
+                };
+                let Some(canvas) = self.canvas.as_mut() else {
+                    // Still image, where frame data is ignored without acTL
+                    if !is_idat {
+                        continue;
+                    }
+                    let mut raster = decode(
+                        data.as_slice(),
+                        &header,
+                        self.palette.as_ref(),
+                        transparency,
+                    )?;
+                    if let Some(color) = &self.color {
+                        color.apply(&mut raster);
+                    }
+                    if let Some(orientation) = self.orientation {
+                        raster = raster.orient(orientation);
+                    }
+                    self.shift(&mut raster);
+                    return Ok(Some(Step {
+                        raster,
+                        delay: 0,
+                        frame: None,
+                    }));
+                };
+                let Some(mut frame) = frame else {
+                    if is_idat {
+                        // Default image, not part of the animation
+                        continue;
+                    }
+                    // Frame data without frame control
+                    return Err(DecoderError::ChunkOrder);
+                };
+                let raster = decode(
+                    data.as_slice(),
+                    &ImageHeader {
+                        width: frame.width,
+                        height: frame.height,
+                        ..header
+                    },
+                    self.palette.as_ref(),
+                    transparency,
+                )?;
+                let mut raster = if self.raw {
+                    raster
+                } else {
+                    canvas.draw(&frame, &raster);
+                    match self.orientation {
+                        Some(orientation) => {
+                            frame.orient(
+                                orientation,
+                                header.width,
+                                header.height,
+                            );
+                            canvas.raster().orient(orientation)
+                        }
+                        None => canvas.raster(),
+                    }
+                };
+                if let Some(color) = &self.color {
+                    color.apply(&mut raster);
+                }
+                self.shift(&mut raster);
+                self.frames += 1;
+                let delay = frame.delay();
+                return Ok(Some(Step {
+                    raster,
+                    delay,
+                    frame: Some(frame),
+                }));
+            }
+
+            // Check for non-required chunks up until the next IDAT or fdAT
+            // chunk or end
+            let chunk = self.decoder.next().unwrap()?;
+            self.ancillary(chunk)?;
         }
+    }
+}
 
-        Some(Ok(Step { raster, delay: 0 }))
+impl<R> Iterator for Steps<R>
+where
+    R: Read,
+{
+    type Item = Result<Step, DecoderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Turn `Result<Option<Step>>` into `Option<Result<Step>>`
+        match self.get_next() {
+            Ok(Some(step)) => Some(Ok(step)),
+            Ok(None) => None,
+            Err(e) => Some(Err(e)),
+        }
     }
 }
 
@@ -330,19 +703,59 @@ pub(crate) fn decode(
     let bit_depth = header.bit_depth;
 
     Ok(match (color_type, bit_depth) {
-        (ColorType::Grey, 8) => {
+        (ColorType::Grey, 1 | 2 | 4 | 8) => {
+            let mut buf = if bit_depth == 8 {
+                buf
+            } else {
+                unpack(&buf, width, height, bit_depth)
+            };
+            // Scale up to 8 bits by repeating the bits
+            let scale = u8::MAX / (u8::MAX >> (8 - bit_depth));
+            if let Some(&Transparency::GrayKey(key)) = transparency {
+                let mut graya = Vec::with_capacity(buf.len() * 2);
+                for value in buf {
+                    graya.push(value * scale);
+                    graya.push(alpha(u16::from(value) == key));
+                }
+                return Ok(PngRaster::Graya8(Raster::with_u8_buffer(
+                    width, height, graya,
+                )));
+            }
+            for value in buf.iter_mut() {
+                *value *= scale;
+            }
             PngRaster::Gray8(Raster::with_u8_buffer(width, height, buf))
         }
         (ColorType::GreyAlpha, 8) => {
             PngRaster::Graya8(Raster::with_u8_buffer(width, height, buf))
         }
         (ColorType::Rgb, 8) => {
+            if let Some(&Transparency::RgbKey(red, green, blue)) = transparency
+            {
+                let key = [red, green, blue];
+                let mut rgba = Vec::with_capacity(buf.len() / 3 * 4);
+                for rgb in buf.chunks_exact(3) {
+                    rgba.extend_from_slice(rgb);
+                    let matches =
+                        rgb.iter().zip(key).all(|(v, k)| u16::from(*v) == k);
+                    rgba.push(alpha(matches));
+                }
+                return Ok(PngRaster::Rgba8(Raster::with_u8_buffer(
+                    width, height, rgba,
+                )));
+            }
             PngRaster::Rgb8(Raster::with_u8_buffer(width, height, buf))
         }
         (ColorType::Rgba, 8) => {
             PngRaster::Rgba8(Raster::with_u8_buffer(width, height, buf))
         }
         (ColorType::Grey, 16) => {
+            if let Some(&Transparency::GrayKey(key)) = transparency {
+                let buf = key16(&buf, &[key]);
+                let mut raster = Raster::with_clear(width, height);
+                raster.as_u8_slice_mut().copy_from_slice(&buf);
+                return Ok(PngRaster::Graya16(raster));
+            }
             let mut raster = Raster::with_clear(width, height);
             for (i, v) in raster.as_u8_slice_mut().iter_mut().enumerate() {
                 *v = buf[i];
@@ -357,6 +770,13 @@ pub(crate) fn decode(
             PngRaster::Graya16(raster)
         }
         (ColorType::Rgb, 16) => {
+            if let Some(&Transparency::RgbKey(red, green, blue)) = transparency
+            {
+                let buf = key16(&buf, &[red, green, blue]);
+                let mut raster = Raster::with_clear(width, height);
+                raster.as_u8_slice_mut().copy_from_slice(&buf);
+                return Ok(PngRaster::Rgba16(raster));
+            }
             let mut raster = Raster::with_clear(width, height);
             for (i, v) in raster.as_u8_slice_mut().iter_mut().enumerate() {
                 *v = buf[i];
@@ -370,18 +790,28 @@ pub(crate) fn decode(
             }
             PngRaster::Rgba16(raster)
         }
-        (ColorType::Palette, 8) => {
-            let palette_slice = palette.as_ref().unwrap().palette.as_slice();
+        (ColorType::Palette, 1 | 2 | 4 | 8) => {
+            let buf = if bit_depth == 8 {
+                buf
+            } else {
+                unpack(&buf, width, height, bit_depth)
+            };
+            let palette_slice =
+                palette.ok_or(DecoderError::NoPalette)?.palette.as_slice();
             let palette_alpha = match transparency {
-                None => Vec::new(),
                 Some(Transparency::Palette(p)) => p.to_vec(),
-                _ => unreachable!(),
+                _ => Vec::new(),
             };
+            // Fill with unique placeholders first, so that duplicate colors
+            // aren't merged into one entry
             let mut palette = Palette::new(palette_slice.len());
-            for (i, color) in palette_slice.iter().enumerate() {
-                let j = palette.set_entry(*color).unwrap();
+            for i in 0..palette_slice.len() {
+                let j = palette.set_entry(SRgb8::new(i as u8, 0, 0)).unwrap();
                 debug_assert_eq!(i, j);
             }
+            for (i, color) in palette_slice.iter().enumerate() {
+                palette.replace_entry(i, *color);
+            }
             debug_assert_eq!(palette_slice.len(), palette.len());
             PngRaster::Palette(
                 Raster::with_u8_buffer(width, height, buf),
@@ -392,3 +822,42 @@ pub(crate) fn decode(
         (ct, bd) => return Err(DecoderError::ColorMode(ct, bd)),
     })
 }
+
+/// Unpack 1, 2 or 4-bit samples (without padding bits between rows) into one
+/// byte per sample.
+fn unpack(buf: &[u8], width: u32, height: u32, bit_depth: u8) -> Vec<u8> {
+    let bits = usize::from(bit_depth);
+    let mask = (1 << bit_depth) - 1;
+    (0..width as usize * height as usize)
+        .map(|i| {
+            let bit = i * bits;
+            (buf[bit / 8] >> (8 - bits - bit % 8)) & mask
+        })
+        .collect()
+}
+
+/// Get the 8-bit alpha value for a pixel, transparent if it matches the key.
+fn alpha(matches: bool) -> u8 {
+    if matches {
+        0
+    } else {
+        u8::MAX
+    }
+}
+
+/// Add an alpha channel to 16-bit (big endian) gray or RGB samples, which is
+/// transparent where the pixel matches the key.
+fn key16(buf: &[u8], key: &[u16]) -> Vec<u8> {
+    let channels = key.len();
+    let mut out = Vec::with_capacity(buf.len() / channels * (channels + 1));
+    for pixel in buf.chunks_exact(channels * 2) {
+        out.extend_from_slice(pixel);
+        let matches = pixel
+            .chunks_exact(2)
+            .zip(key)
+            .all(|(v, k)| u16::from_be_bytes([v[0], v[1]]) == *k);
+        let alpha = alpha(matches);
+        out.extend_from_slice(&[alpha, alpha]);
+    }
+    out
+}
@@ -1,9 +1,5 @@
 use crate::{
-    adam7,
-    bitstream::{BitstreamReader, BitstreamWriter},
-    chunk::ImageHeader,
-    decode::Error as DecoderError,
-    encode::filter,
+    adam7, chunk::ImageHeader, decode::Error as DecoderError, encode::filter,
 };
 
 /*out must be buffer big enough to contain full image, and in must contain the full decompressed data from
@@ -79,27 +75,12 @@ fn remove_padding_bits_aliased(
     ilinebits: usize,
     h: usize,
 ) {
-    let diff = ilinebits - olinebits; /* input and output bit pointers */
-    let mut ibp = 0;
-    let mut out = Vec::with_capacity(olinebits);
-    let mut out_stream = BitstreamWriter::new(&mut out);
-    for _ in 0..h {
-        for _ in 0..olinebits {
-            let bit = {
-                let mut in_stream = BitstreamReader::with_bitpointer(
-                    std::io::Cursor::new(&inout[in_off..]),
-                    ibp,
-                )
-                .unwrap();
-                in_stream.read().unwrap().unwrap()
-            };
-            out_stream.write(bit).unwrap();
+    // Output is never ahead of input, so bits can be moved in place
+    for y in 0..h {
+        for i in 0..olinebits {
+            let bit = get_bit(inout, in_off * 8 + y * ilinebits + i);
+            set_bit(inout, out_off * 8 + y * olinebits + i, bit);
         }
-        ibp += olinebits + diff;
-    }
-    // Copy output buffer into array.
-    for (i, byte) in out.iter().cloned().enumerate() {
-        inout[out_off + i] = byte;
     }
 }
 
@@ -150,27 +131,25 @@ fn remove_padding_bits(
     ilinebits: usize,
     h: usize,
 ) {
-    let diff = ilinebits - olinebits; /* input and output bit pointers */
-    let mut ibp = 0;
-    let mut out_buf = Vec::new();
-    let mut out_stream = BitstreamWriter::new(&mut out_buf);
-    for _ in 0..h {
-        for _ in 0..olinebits {
-            let bit = {
-                let mut in_stream = BitstreamReader::with_bitpointer(
-                    std::io::Cursor::new(inp),
-                    ibp,
-                )
-                .unwrap();
-                in_stream.read().unwrap().unwrap()
-            };
-            out_stream.write(bit).unwrap();
+    for y in 0..h {
+        for i in 0..olinebits {
+            set_bit(out, y * olinebits + i, get_bit(inp, y * ilinebits + i));
         }
-        ibp += olinebits + diff;
     }
-    // Copy output buffer into array.
-    for (i, byte) in out_buf.iter().cloned().enumerate() {
-        out[i] = byte;
+}
+
+/// Get a bit, starting from the most significant bit of the first byte.
+fn get_bit(buf: &[u8], bit: usize) -> bool {
+    (buf[bit / 8] >> (7 - bit % 8)) & 1 != 0
+}
+
+/// Set a bit, starting from the most significant bit of the first byte.
+fn set_bit(buf: &mut [u8], bit: usize, value: bool) {
+    let mask = 1 << (7 - bit % 8);
+    if value {
+        buf[bit / 8] |= mask;
+    } else {
+        buf[bit / 8] &= !mask;
     }
 }
 
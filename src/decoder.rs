@@ -1,8 +1,9 @@
 use std::io::{ErrorKind, Read};
 
 use crate::{
+    chunk::{Codec, CustomChunk, ImageHeader},
     consts,
-    decode::{Chunks, Error, Result, Steps},
+    decode::{Chunks, Error, Frames, Result, Steps},
     Step,
 };
 
@@ -17,6 +18,10 @@ pub(crate) struct Parser<R: Read> {
     decode: Decoder<R>,
     /// Palette chunk found?
     palette: bool,
+    /// Image header, once found
+    header: Option<ImageHeader>,
+    /// Next expected APNG sequence number
+    sequence: u32,
 }
 
 impl<R: Read> Parser<R> {
@@ -52,6 +57,54 @@ impl<R: Read> Parser<R> {
         self.palette
     }
 
+    /// Call this when the image header chunk is found.
+    pub(crate) fn set_header(&mut self, header: ImageHeader) {
+        self.header = Some(header);
+    }
+
+    /// Get the image header, if it's been parsed yet.
+    pub(crate) fn header(&self) -> Option<&ImageHeader> {
+        self.header.as_ref()
+    }
+
+    /// Get the parser of a registered user-defined chunk type.
+    pub(crate) fn custom(&self, name: [u8; 4]) -> Option<Codec> {
+        self.decode
+            .custom
+            .iter()
+            .find(|codec| codec.name == name)
+            .copied()
+    }
+
+    /// Whether or not pixels are converted to sRGB.
+    pub(crate) fn convert_srgb(&self) -> bool {
+        self.decode.srgb
+    }
+
+    /// Whether or not the EXIF orientation is applied.
+    pub(crate) fn orient(&self) -> bool {
+        self.decode.orient
+    }
+
+    /// Whether or not samples are shifted to their significant bits.
+    pub(crate) fn shift_significant_bits(&self) -> bool {
+        self.decode.shift
+    }
+
+    /// Whether or not tRNS color keys are kept as metadata.
+    pub(crate) fn keep_key(&self) -> bool {
+        self.decode.keep_key
+    }
+
+    /// Check that an APNG sequence number is the next one in the sequence.
+    pub(crate) fn sequence(&mut self, sequence: u32) -> Result<()> {
+        if sequence != self.sequence {
+            return Err(Error::Sequence(sequence));
+        }
+        self.sequence += 1;
+        Ok(())
+    }
+
     /// Get the length of the chunk.
     pub(crate) fn len(&self) -> usize {
         self.length.try_into().unwrap()
@@ -131,27 +184,24 @@ impl<R: Read> Parser<R> {
         Ok(out)
     }
 
-    /// Get a String out of a reader
-    pub(crate) fn string(&mut self, length: usize) -> Result<String> {
-        let mut bytes = [0u8; 4];
-        let mut index = 0;
+    /// Get a Null terminated ISO 8859-1 (Latin-1) String out of a reader
+    pub(crate) fn latin1_str(&mut self) -> Result<String> {
         let mut out = String::new();
-        for _ in 0..length {
+        loop {
             let byte = self.u8()?;
-            bytes[index] = byte;
-            index += 1;
-            match std::str::from_utf8(&bytes[0..index]) {
-                Ok(c) => {
-                    out.push_str(c);
-                    index = 0;
-                }
-                Err(e) => {
-                    if e.error_len().is_some() {
-                        out.push(std::char::REPLACEMENT_CHARACTER);
-                        index = 0;
-                    }
-                }
+            if byte == 0 {
+                break;
             }
+            out.push(byte.into());
+        }
+        Ok(out)
+    }
+
+    /// Get an ISO 8859-1 (Latin-1) String out of a reader
+    pub(crate) fn latin1(&mut self, length: usize) -> Result<String> {
+        let mut out = String::with_capacity(length);
+        for _ in 0..length {
+            out.push(self.u8()?.into());
         }
         Ok(out)
     }
@@ -169,12 +219,14 @@ impl<R: Read> Parser<R> {
 
 /// PNG file decoder
 ///
-/// Can be converted into one of two iterators:
+/// Can be converted into one of three iterators:
 /// - [into_iter] / [into_steps] for high-level [Step]s
+/// - [into_frames] for raw APNG frames as [Step]s
 /// - [into_chunks] for low-level [Chunk]s
 ///
 /// [into_iter]: struct.Decoder.html#method.into_iter
 /// [into_steps]: struct.Decoder.html#method.into_steps
+/// [into_frames]: struct.Decoder.html#method.into_frames
 /// [into_chunks]: struct.Decoder.html#method.into_chunks
 /// [Step]: struct.Step.html
 /// [Chunk]: chunk/enum.Chunk.html
@@ -182,6 +234,16 @@ impl<R: Read> Parser<R> {
 pub struct Decoder<R: Read> {
     // The source of PNG input.
     reader: R,
+    // Keep tRNS color keys as metadata rather than applying them.
+    keep_key: bool,
+    // Convert pixels to sRGB using the color space metadata.
+    srgb: bool,
+    // Rotate and flip images using the EXIF orientation.
+    orient: bool,
+    // Shift samples right to their significant bits (sBIT).
+    shift: bool,
+    // Registered user-defined chunk types.
+    custom: Vec<Codec>,
 }
 
 impl<R: Read> Decoder<R> {
@@ -190,11 +252,68 @@ impl<R: Read> Decoder<R> {
         // Read first 8 bytes (PNG Signature)
         let mut buf = [0u8; 8];
         reader.read_exact(&mut buf).map_err(Error::from)?;
-        if buf != crate::consts::PNG_SIGNATURE {
+        if buf != consts::PNG_SIGNATURE {
             return Err(Error::InvalidSignature);
         }
 
-        Ok(Decoder { reader })
+        Ok(Decoder {
+            reader,
+            keep_key: false,
+            srgb: false,
+            orient: false,
+            shift: false,
+            custom: Vec::new(),
+        })
+    }
+
+    /// Keep tRNS color keys of gray and RGB images as metadata (see
+    /// [`Steps::transparency`](decode/struct.Steps.html#method.transparency))
+    /// instead of decoding them as an alpha channel (default).
+    pub fn keep_transparency_key(mut self) -> Self {
+        self.keep_key = true;
+        self
+    }
+
+    /// Convert pixels from the color space described by the iCCP, or gAMA and
+    /// cHRM chunks into sRGB (default is to return the samples as stored).
+    /// Images with an sRGB chunk are already sRGB, and images with a cICP
+    /// chunk are left as stored.  Only matrix/TRC and gray
+    /// TRC ICC profiles are supported; other profiles are reported as
+    /// [`Error::ProfileLut`] or [`Error::ColorProfile`] when decoding.
+    pub fn convert_to_srgb(mut self) -> Self {
+        self.srgb = true;
+        self
+    }
+
+    /// Rotate and flip images so they display upright according to the EXIF
+    /// orientation of an eXIf chunk before the image data (default is to
+    /// return them as stored).  Applies to still images and composited
+    /// animation frames (including the region of `Step::frame`), but not to
+    /// frames from [`into_frames`](#method.into_frames).
+    pub fn apply_orientation(mut self) -> Self {
+        self.orient = true;
+        self
+    }
+
+    /// Shift samples right to the precision of the original data according to
+    /// the sBIT chunk (default is to return samples at the image's full
+    /// range).  For example, 12-bit data stored in a 16-bit image is returned
+    /// as values from 0 to 4095.  Palette colors are shifted rather than
+    /// the indices.
+    pub fn shift_significant_bits(mut self) -> Self {
+        self.shift = true;
+        self
+    }
+
+    /// Register a user-defined chunk type, so chunks with its name are decoded
+    /// as a [`Chunk::Custom`](chunk/enum.Chunk.html#variant.Custom) rather
+    /// than a `Chunk::Unknown` (chunks this crate supports can't be replaced).
+    /// [`Steps`](decode/struct.Steps.html) keep custom chunks in
+    /// `PngMetadata::custom_chunks`, and return a `decode::Error::ChunkOrder`
+    /// for ones that aren't in their position.
+    pub fn custom_chunk<C: CustomChunk>(mut self) -> Self {
+        self.custom.push(Codec::new::<C>());
+        self
     }
 
     /// Convert into a `Chunk` iterator.
@@ -207,6 +326,11 @@ impl<R: Read> Decoder<R> {
         Steps::new(self.into_chunks())
     }
 
+    /// Convert into a raw (uncomposited) APNG frame iterator.
+    pub fn into_frames(self) -> Frames<R> {
+        Frames::new(self.into_chunks())
+    }
+
     /// Convert into a `Parser`.
     fn parser(self) -> Parser<R> {
         Parser {
@@ -214,6 +338,8 @@ impl<R: Read> Decoder<R> {
             length: 0,
             chksum: 0,
             palette: false,
+            header: None,
+            sequence: 0,
         }
     }
 }
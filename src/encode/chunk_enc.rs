@@ -1,31 +1,69 @@
 use std::io::Write;
 
-use crate::{chunk::Chunk, encode::Error, encoder::Enc};
+use crate::{
+    chunk::{Chunk, ChunkPosition, ImageHeader},
+    consts,
+    encode::Error,
+    encoder::Enc,
+};
 
 /// Chunk Encoder for PNG files.
 ///
 /// Note that this doesn't enforce correct ordering of chunks or valid chunk
-/// combinations.  If you need it, use `StepEncoder`, the higher-level API.
+/// combinations, except for the position of custom chunks.  If you need it,
+/// use `StepEncoder`, the higher-level API.
+///
+/// The PNG signature is written with the `ImageHeader` chunk, so it must not
+/// be written to the output beforehand (previously, callers wrote it).
 #[derive(Debug)]
 pub struct ChunkEnc<W: Write> {
     // FIXME: use .encode() instead of pub(crate).
     pub(crate) enc: Enc<W>,
+    // Image header, once encoded
+    header: Option<ImageHeader>,
+    // Next APNG sequence number
+    sequence: u32,
+    // Region of the file the next chunk is in
+    position: ChunkPosition,
 }
 
 impl<W: Write> ChunkEnc<W> {
     /// Create a new encoder.
     pub(crate) fn new(enc: Enc<W>) -> Self {
-        Self { enc }
+        Self {
+            enc,
+            header: None,
+            sequence: 0,
+            position: ChunkPosition::BeforePalette,
+        }
     }
 
     /// Encode one [`Chunk`](struct.Chunk.html)
+    ///
+    /// The PNG signature is written before the `ImageHeader` chunk.
+    /// APNG `FrameControl` and `FrameData` chunks are renumbered in the order
+    /// they are encoded, and `FrameControl` regions must fit within the
+    /// previously encoded `ImageHeader`.  `Custom` chunks must be of a type
+    /// registered on the `Encoder`, and in their position.
     pub fn encode(&mut self, chunk: &mut Chunk) -> Result<(), Error> {
         use Chunk::*;
         match chunk {
-            ImageHeader(image_header) => image_header.write(&mut self.enc),
-            ImageData(image_data) => image_data.write(&mut self.enc),
+            ImageHeader(image_header) => {
+                self.header = Some(*image_header);
+                self.position = ChunkPosition::BeforePalette;
+                self.enc.raw(&consts::PNG_SIGNATURE)?;
+                image_header.write(&mut self.enc)
+            }
+            ImageData(image_data) => {
+                self.position = ChunkPosition::AfterImageData;
+                image_data.write(&mut self.enc)
+            }
             ImageEnd(image_end) => image_end.write(&mut self.enc),
-            Palette(palette) => palette.write(&mut self.enc),
+            Palette(palette) => {
+                self.position =
+                    self.position.max(ChunkPosition::BeforeImageData);
+                palette.write(&mut self.enc)
+            }
             Background(background) => background.write(&mut self.enc),
             InternationalText(itext) => itext.write(&mut self.enc),
             Physical(physical) => physical.write(&mut self.enc),
@@ -33,7 +71,56 @@ impl<W: Write> ChunkEnc<W> {
             Time(time) => time.write(&mut self.enc),
             Transparency(transparency) => transparency.write(&mut self.enc),
             CompressedText(ztext) => ztext.write(&mut self.enc),
+            Gamma(gamma) => gamma.write(&mut self.enc),
+            Chromaticities(chrm) => chrm.write(&mut self.enc),
+            SRgb(srgb) => srgb.write(&mut self.enc),
+            ColorProfile(iccp) => iccp.write(&mut self.enc),
+            CodePoints(cicp) => cicp.write(&mut self.enc),
+            MasteringDisplay(mdcv) => mdcv.write(&mut self.enc),
+            ContentLightLevel(clli) => clli.write(&mut self.enc),
+            Exif(exif) => exif.write(&mut self.enc),
+            SignificantBits(sbit) => sbit.write(&mut self.enc),
+            SuggestedPalette(splt) => splt.write(&mut self.enc),
+            PaletteHistogram(hist) => hist.write(&mut self.enc),
+            ImageOffset(offs) => offs.write(&mut self.enc),
+            PixelCalibration(pcal) => pcal.write(&mut self.enc),
+            SubjectPhysical(scal) => scal.write(&mut self.enc),
+            Stereo(ster) => ster.write(&mut self.enc),
+            GifGraphicControlExt(gifg) => gifg.write(&mut self.enc),
+            GifApplicationExt(gifx) => gifx.write(&mut self.enc),
+            AnimationControl(actl) => actl.write(&mut self.enc),
+            FrameControl(fctl) => {
+                let header = self
+                    .header
+                    .ok_or(Error::ChunkOrder(consts::FRAME_CONTROL))?;
+                if !fctl.fits(&header) {
+                    return Err(Error::FrameRegion);
+                }
+                fctl.sequence = self.next_sequence();
+                fctl.write(&mut self.enc)
+            }
+            FrameData(fdat) => {
+                self.position = ChunkPosition::AfterImageData;
+                fdat.sequence = self.next_sequence();
+                fdat.write(&mut self.enc)
+            }
+            Custom(custom) => {
+                if !self.enc.is_custom(custom.name()) {
+                    return Err(Error::UnregisteredChunk(custom.name()));
+                }
+                if self.header.is_none() || self.position > custom.position() {
+                    return Err(Error::ChunkOrder(custom.name()));
+                }
+                custom.write(&mut self.enc)
+            }
             Unknown(unknown) => unknown.write(&mut self.enc),
         }
     }
+
+    /// Get the next APNG sequence number.
+    fn next_sequence(&mut self) -> u32 {
+        let sequence = self.sequence;
+        self.sequence += 1;
+        sequence
+    }
 }
@@ -10,6 +10,7 @@ impl From<std::io::Error> for Error {
 /// Encoding Errors.
 #[derive(Debug)]
 #[allow(variant_size_differences)]
+#[non_exhaustive]
 pub enum Error {
     /// A wrapped I/O error.
     Io(std::sync::Arc<std::io::Error>),
@@ -24,6 +25,31 @@ pub enum Error {
     /// Chunks arranged in invalid sequence.  Provides PNG chunk identifier of
     /// the out-of-order chunk.
     ChunkOrder([u8; 4]),
+    /// APNG frame region is empty or doesn't fit within the image
+    FrameRegion,
+    /// APNG frame has a different color type, bit depth or palette than the
+    /// first frame
+    FrameFormat,
+    /// Number of encoded APNG frames doesn't match the animation frame count
+    FrameCount,
+    /// APNG frame delay (milliseconds) is too long to encode
+    FrameDelay(u32),
+    /// Palette index or gray value doesn't fit in the bit depth
+    BitDepth(u8),
+    /// tRNS color key doesn't match the color type or bit depth
+    TransparencyKey,
+    /// bKGD color doesn't match the color type, palette or bit depth
+    Background,
+    /// Keyword or tEXt/zTXt text can't be represented in ISO 8859-1 (Latin-1)
+    Latin1,
+    /// Chunk field value is out of range.  Provides PNG chunk identifier of
+    /// the chunk.
+    ChunkValue([u8; 4]),
+    /// Custom chunk type isn't registered on the `Encoder`.  Provides PNG
+    /// chunk identifier of the chunk.
+    UnregisteredChunk([u8; 4]),
+    /// `StepEnc` was already finished
+    Finished,
 }
 
 impl std::fmt::Display for Error {
@@ -42,6 +68,32 @@ impl std::fmt::Display for Error {
                 "Chunk {} out of order",
                 String::from_utf8_lossy(bytes)
             ),
+            FrameRegion => write!(
+                f,
+                "Frame region is empty or doesn't fit within the image"
+            ),
+            FrameFormat => write!(f, "Frame format doesn't match first frame"),
+            FrameCount => {
+                write!(f, "Frame count doesn't match animation frame count")
+            }
+            FrameDelay(delay) => write!(f, "Frame delay {delay}ms too long"),
+            BitDepth(bit_depth) => {
+                write!(f, "Sample doesn't fit in bit depth {bit_depth}")
+            }
+            TransparencyKey => write!(f, "Invalid transparency key"),
+            Background => write!(f, "Invalid background color"),
+            Latin1 => write!(f, "Text can't be represented in Latin-1"),
+            ChunkValue(bytes) => write!(
+                f,
+                "{} chunk value out of range",
+                String::from_utf8_lossy(bytes)
+            ),
+            UnregisteredChunk(bytes) => write!(
+                f,
+                "{} custom chunk type not registered",
+                String::from_utf8_lossy(bytes)
+            ),
+            Finished => write!(f, "Encoder was already finished"),
         }
     }
 }
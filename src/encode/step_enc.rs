@@ -1,4 +1,4 @@
-use std::{any::TypeId, io::Write};
+use std::{any::TypeId, collections::HashMap, io::Write};
 
 use pix::{
     el::Pixel,
@@ -10,13 +10,20 @@ use pix::{
 use crate::{
     adam7,
     bitstream::{BitstreamReader, BitstreamWriter},
+    canvas::Canvas,
     chunk::{
-        ColorType, ImageData, ImageEnd, ImageHeader, Palette as PaletteChunk,
-        Transparency,
+        AnimationControl, Background, BlendOp, Chromaticities, Chunk,
+        ChunkPosition, CodePoints, ColorProfile, ColorType, CompressedText,
+        ContentLightLevel, Custom, DisposeOp, Exif, FrameControl, FrameData,
+        Gamma, GifApplicationExt, GifGraphicControlExt, ImageData, ImageEnd,
+        ImageHeader, ImageOffset, InternationalText, MasteringDisplay,
+        Palette as PaletteChunk, PaletteHistogram, Physical, PixelCalibration,
+        SRgb, SignificantBits, Stereo, SubjectPhysical, SuggestedColor,
+        SuggestedPalette, Text, Time, Transparency, Unknown,
     },
+    consts,
     encode::{filter, ChunkEnc, Error as EncoderError, FilterStrategy, Result},
-    encoder::Enc,
-    PngRaster, Step,
+    zlib, PngMetadata, PngRaster, Step, TextEntry,
 };
 
 pub trait AsRaster {
@@ -24,6 +31,7 @@ pub trait AsRaster {
     fn get_u8_slice(&self) -> &[u8];
     fn get_palette_colors(&self) -> &[SRgb8];
     fn get_palette_alphas(&self) -> &[u8];
+    fn to_png_raster(&self) -> PngRaster;
 }
 
 impl AsRaster for PngRaster {
@@ -61,6 +69,10 @@ impl AsRaster for PngRaster {
             _ => &[],
         }
     }
+
+    fn to_png_raster(&self) -> PngRaster {
+        self.clone()
+    }
 }
 
 impl<P: Pixel> AsRaster for Raster<P> {
@@ -105,17 +117,85 @@ impl<P: Pixel> AsRaster for Raster<P> {
     fn get_palette_alphas(&self) -> &[u8] {
         &[]
     }
+
+    fn to_png_raster(&self) -> PngRaster {
+        fn copy<Q: Pixel>(raster: &[u8], width: u32, height: u32) -> Raster<Q> {
+            let mut out = Raster::with_clear(width, height);
+            out.as_u8_slice_mut().copy_from_slice(raster);
+            out
+        }
+
+        let header = self.get_header(false);
+        let (samples, width, height) =
+            (self.as_u8_slice(), self.width(), self.height());
+        match (header.color_type, header.bit_depth) {
+            (ColorType::Grey, 8) => {
+                PngRaster::Gray8(copy(samples, width, height))
+            }
+            (ColorType::Grey, _) => {
+                PngRaster::Gray16(copy(samples, width, height))
+            }
+            (ColorType::GreyAlpha, 8) => {
+                PngRaster::Graya8(copy(samples, width, height))
+            }
+            (ColorType::GreyAlpha, _) => {
+                PngRaster::Graya16(copy(samples, width, height))
+            }
+            (ColorType::Rgb, 8) => {
+                PngRaster::Rgb8(copy(samples, width, height))
+            }
+            (ColorType::Rgb, _) => {
+                PngRaster::Rgb16(copy(samples, width, height))
+            }
+            (ColorType::Rgba, 8) => {
+                PngRaster::Rgba8(copy(samples, width, height))
+            }
+            (_, _) => PngRaster::Rgba16(copy(samples, width, height)),
+        }
+    }
 }
 
 /// Frame Encoder for PNG files.
+///
+/// Encodes either a still PNG (with [`still`](#method.still), or a single call
+/// to [`encode`](#method.encode)), or an APNG animation after calling
+/// [`animate`](#method.animate).  Animations must be completed with
+/// [`finish`](#method.finish).
 #[derive(Debug)]
 pub struct StepEnc<W: Write> {
     encoder: ChunkEnc<W>,
-    // FIXME
-    #[allow(dead_code)]
-    coldepth: Option<(ColorType, u32)>,
-    #[allow(dead_code)]
-    header: Option<ImageHeader>,
+    // Some if encoding an APNG animation.
+    animation: Option<AnimationControl>,
+    // Image header and palette of the first frame.
+    header: Option<(ImageHeader, Vec<SRgb8>)>,
+    // Number of frames encoded so far.
+    frames: u32,
+    // Frame delay denominator.
+    delay_den: u16,
+    // Frame disposal.
+    dispose_op: DisposeOp,
+    // Frame blending.
+    blend_op: BlendOp,
+    // What the animation looks like after the last encoded frame.
+    canvas: Option<Canvas>,
+    // Some if optimizing frames, true if `BlendOp::Over` may be used.
+    optimize: Option<bool>,
+    // Optimized frame waiting for the next frame to choose it's disposal.
+    pending: Option<(FrameControl, Vec<u8>)>,
+    // Number of frames written so far.
+    written: u32,
+    // tRNS color key.
+    transparency: Option<Transparency>,
+    // Ancillary metadata.
+    metadata: PngMetadata,
+    // True if samples are scaled up from their significant bits.
+    scale: bool,
+    // Some if generating a suggested palette, with the maximum colors.
+    suggest: Option<u16>,
+    // True if unknown chunks that aren't safe to copy are kept.
+    copy_unsafe: bool,
+    // True once `finish` has been called.
+    finished: bool,
 }
 
 impl<W: Write> StepEnc<W> {
@@ -123,39 +203,699 @@ impl<W: Write> StepEnc<W> {
     pub(crate) fn new(encoder: ChunkEnc<W>) -> Self {
         Self {
             encoder,
-            coldepth: None,
+            animation: None,
             header: None,
+            frames: 0,
+            delay_den: 1000,
+            dispose_op: DisposeOp::None,
+            blend_op: BlendOp::Source,
+            canvas: None,
+            optimize: None,
+            pending: None,
+            written: 0,
+            transparency: None,
+            metadata: PngMetadata::default(),
+            scale: false,
+            suggest: None,
+            copy_unsafe: false,
+            finished: false,
+        }
+    }
+
+    /// Encode an APNG animation of `num_frames` frames, played `num_plays`
+    /// times (0 loops forever).
+    pub fn animate(mut self, num_frames: u32, num_plays: u32) -> Self {
+        self.animation = Some(AnimationControl {
+            num_frames,
+            num_plays,
+        });
+        self
+    }
+
+    /// Set the denominator of frame delays (default: 1000).  Each
+    /// [`Step`](struct.Step.html)'s delay in milliseconds is rounded to a
+    /// multiple of `1 / delay_den` seconds.  Must not be 0.
+    pub fn delay_den(mut self, delay_den: u16) -> Self {
+        assert_ne!(delay_den, 0);
+        self.delay_den = delay_den;
+        self
+    }
+
+    /// Set how each frame's region is disposed of after it's displayed
+    /// (default: `DisposeOp::None`).
+    pub fn dispose_op(mut self, dispose_op: DisposeOp) -> Self {
+        self.dispose_op = dispose_op;
+        self
+    }
+
+    /// Set how each frame is blended onto the previous frame (default:
+    /// `BlendOp::Source`).
+    pub fn blend_op(mut self, blend_op: BlendOp) -> Self {
+        self.blend_op = blend_op;
+        self
+    }
+
+    /// Set the tRNS color key of gray and RGB images: pixels matching the key
+    /// are transparent.  The key is in the raster's sample values (converted
+    /// if the bit depth is lowered), and must be a `Transparency::GrayKey` for
+    /// gray rasters or a `Transparency::RgbKey` for RGB rasters.
+    pub fn transparency_key(mut self, key: Transparency) -> Self {
+        self.transparency = Some(key);
+        self
+    }
+
+    /// Set the metadata (text, time, pHYs, bKGD, color space, HDR, EXIF, sBIT,
+    /// sPLT, hIST, the oFFs, pCAL, sCAL, sTER, gIFg and gIFx extensions, and
+    /// unknown and custom chunks), for example from
+    /// [`Steps::metadata`](../decode/struct.Steps.html#method.metadata).
+    ///
+    /// A `Background::Gray` color is in the raster's sample values (converted
+    /// if the bit depth is lowered).  Unknown and custom chunks are written in
+    /// the same region they were decoded from.  Unknown critical chunks are
+    /// never written, and unknown chunks that aren't safe to copy only with
+    /// [`copy_unsafe_chunks`](#method.copy_unsafe_chunks).  Custom chunk types
+    /// must be registered with
+    /// [`Encoder::custom_chunk`](../struct.Encoder.html#method.custom_chunk).
+    pub fn metadata(mut self, metadata: PngMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Add a text entry.  Entries with a language tag or translated keyword,
+    /// or text that can't be represented in Latin-1, are written as iTXt.
+    /// Otherwise compressed entries are written as zTXt and the rest as tEXt.
+    /// Keywords must be Latin-1.
+    pub fn text(mut self, entry: TextEntry) -> Self {
+        self.metadata.text.push(entry);
+        self
+    }
+
+    /// Set the last modification time (tIME).
+    pub fn time(mut self, time: Time) -> Self {
+        self.metadata.time = Some(time);
+        self
+    }
+
+    /// Set the physical pixel dimensions (pHYs).
+    pub fn physical(mut self, physical: Physical) -> Self {
+        self.metadata.physical = Some(physical);
+        self
+    }
+
+    /// Set the suggested background color (bKGD), which must be a
+    /// `Background::Palette` index for palette rasters, a `Background::Gray`
+    /// value for gray rasters, or a `Background::Rgb` color for RGB rasters.
+    pub fn background(mut self, background: Background) -> Self {
+        self.metadata.background = Some(background);
+        self
+    }
+
+    /// Set the image gamma (gAMA).
+    pub fn gamma(mut self, gamma: Gamma) -> Self {
+        self.metadata.gamma = Some(gamma);
+        self
+    }
+
+    /// Set the primary chromaticities and white point (cHRM).
+    pub fn chromaticities(mut self, chromaticities: Chromaticities) -> Self {
+        self.metadata.chromaticities = Some(chromaticities);
+        self
+    }
+
+    /// Mark the image as being in the sRGB color space (sRGB).
+    pub fn srgb(mut self, srgb: SRgb) -> Self {
+        self.metadata.srgb = Some(srgb);
+        self
+    }
+
+    /// Embed an ICC profile (iCCP).
+    pub fn color_profile(mut self, color_profile: ColorProfile) -> Self {
+        self.metadata.color_profile = Some(color_profile);
+        self
+    }
+
+    /// Set the coding-independent code points (cICP).
+    pub fn code_points(mut self, code_points: CodePoints) -> Self {
+        self.metadata.code_points = Some(code_points);
+        self
+    }
+
+    /// Set the mastering display color volume (mDCv).
+    pub fn mastering_display(
+        mut self,
+        mastering_display: MasteringDisplay,
+    ) -> Self {
+        self.metadata.mastering_display = Some(mastering_display);
+        self
+    }
+
+    /// Set the content light level (cLLi).
+    pub fn light_level(mut self, light_level: ContentLightLevel) -> Self {
+        self.metadata.light_level = Some(light_level);
+        self
+    }
+
+    /// Set the EXIF data (eXIf).
+    pub fn exif(mut self, exif: Exif) -> Self {
+        self.metadata.exif = Some(exif);
+        self
+    }
+
+    /// Set the significant bits of each channel (sBIT).
+    pub fn significant_bits(
+        mut self,
+        significant_bits: SignificantBits,
+    ) -> Self {
+        self.metadata.significant_bits = Some(significant_bits);
+        self
+    }
+
+    /// Add a suggested palette (sPLT).  Names must be unique.
+    pub fn suggested_palette(mut self, palette: SuggestedPalette) -> Self {
+        self.metadata.suggested_palettes.push(palette);
+        self
+    }
+
+    /// Set the frequencies of the palette entries (hIST), only for palette
+    /// rasters.
+    pub fn histogram(mut self, histogram: PaletteHistogram) -> Self {
+        self.metadata.histogram = Some(histogram);
+        self
+    }
+
+    /// Set the position of the image on a page (oFFs).
+    pub fn offset(mut self, offset: ImageOffset) -> Self {
+        self.metadata.offset = Some(offset);
+        self
+    }
+
+    /// Set the mapping of sample values to physical values (pCAL).
+    pub fn calibration(mut self, calibration: PixelCalibration) -> Self {
+        self.metadata.calibration = Some(calibration);
+        self
+    }
+
+    /// Set the physical size of the subject covered by each pixel (sCAL).
+    pub fn subject_physical(
+        mut self,
+        subject_physical: SubjectPhysical,
+    ) -> Self {
+        self.metadata.subject_physical = Some(subject_physical);
+        self
+    }
+
+    /// Mark the image as a stereo pair (sTER).
+    pub fn stereo(mut self, stereo: Stereo) -> Self {
+        self.metadata.stereo = Some(stereo);
+        self
+    }
+
+    /// Add a GIF graphic control extension (gIFg).
+    pub fn gif_graphic_control(mut self, ext: GifGraphicControlExt) -> Self {
+        self.metadata.gif_graphic_controls.push(ext);
+        self
+    }
+
+    /// Add a GIF application extension (gIFx).
+    pub fn gif_application(mut self, ext: GifApplicationExt) -> Self {
+        self.metadata.gif_applications.push(ext);
+        self
+    }
+
+    /// Add an unknown ancillary chunk, written in `position`.
+    pub fn unknown_chunk(
+        mut self,
+        position: ChunkPosition,
+        chunk: Unknown,
+    ) -> Self {
+        self.metadata.unknown_chunks.push((position, chunk));
+        self
+    }
+
+    /// Add a chunk of a custom type, written in `position`.  The type must be
+    /// registered with
+    /// [`Encoder::custom_chunk`](../struct.Encoder.html#method.custom_chunk).
+    pub fn custom_chunk(
+        mut self,
+        position: ChunkPosition,
+        chunk: Custom,
+    ) -> Self {
+        self.metadata.custom_chunks.push((position, chunk));
+        self
+    }
+
+    /// Also write unknown chunks that aren't safe to copy, which depend on
+    /// the image data.  Only use this if the pixels and palette are unchanged
+    /// from the file the chunks were decoded from.
+    pub fn copy_unsafe_chunks(mut self) -> Self {
+        self.copy_unsafe = true;
+        self
+    }
+
+    /// Generate a suggested palette (sPLT) of up to `max_colors` of the most
+    /// frequent colors of gray and RGB rasters, or a histogram (hIST) of
+    /// palette rasters.  It's generated from the still image, or the first
+    /// frame of an animation.
+    pub fn suggest_palette(mut self, max_colors: u16) -> Self {
+        self.suggest = Some(max_colors);
+        self
+    }
+
+    /// Scale samples up from the precision given by
+    /// [`significant_bits`](#method.significant_bits) to the full range of the
+    /// raster (default is to encode samples as they are).  For example, 12-bit
+    /// values from 0 to 4095 in a 16-bit raster are scaled up to 0 to 65535.
+    /// Palette colors are scaled rather than the indices.  The transparency key
+    /// and background color aren't scaled.
+    pub fn scale_significant_bits(mut self) -> Self {
+        self.scale = true;
+        self
+    }
+
+    /// Optimize animations by only encoding the region of each frame that
+    /// changed from the previous frame, choosing the dispose operation that
+    /// makes the next frame the smallest.  If `over` is true, unchanged pixels
+    /// may be made transparent to blend frames with `BlendOp::Over`.
+    ///
+    /// Frames must be full-size rasters, and only the timing is used from
+    /// their frame control.  Decoded frames are identical to the encoded
+    /// rasters.
+    pub fn optimize(mut self, over: bool) -> Self {
+        self.optimize = Some(over);
+        self
+    }
+
+    /// Get the gray values of the tRNS key and bKGD.
+    fn grays(&self) -> Vec<u16> {
+        let mut grays = Vec::new();
+        if let Some(Transparency::GrayKey(gray)) = self.transparency {
+            grays.push(gray);
+        }
+        if let Some(Background::Gray(gray)) = self.metadata.background {
+            grays.push(gray);
         }
+        grays
     }
 
     /// Encode a still (takes either a `png_pong::PngRaster` or `pix::Raster`).
     pub fn still<R: AsRaster>(&mut self, raster: &R) -> Result<()> {
-        let image_header = raster.get_header(self.encoder.enc.interlace());
+        match self.scaled(raster)? {
+            Some(raster) => self.encode_still(&raster),
+            None => self.encode_still(raster),
+        }
+    }
+
+    /// Scale a raster up from its significant bits, `None` if not enabled.
+    fn scaled<R: AsRaster>(&self, raster: &R) -> Result<Option<PngRaster>> {
+        match self.metadata.significant_bits {
+            Some(significant_bits) if self.scale => {
+                scale_up(raster, significant_bits).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Add a generated suggested palette or histogram to the metadata, if
+    /// enabled.
+    fn suggest<R: AsRaster>(&mut self, raster: &R) {
+        let Some(max_colors) = self.suggest.take() else {
+            return;
+        };
+        let header = raster.get_header(false);
+        let samples = raster.get_u8_slice();
+        if header.color_type == ColorType::Palette {
+            let mut counts = vec![0; raster.get_palette_colors().len()];
+            for index in samples {
+                if let Some(count) = counts.get_mut(usize::from(*index)) {
+                    *count += 1;
+                }
+            }
+            self.metadata.histogram = Some(PaletteHistogram {
+                frequencies: frequencies(&counts),
+            });
+            return;
+        }
+
+        let wide = header.bit_depth == 16;
+        let channels = match header.color_type {
+            ColorType::Grey => 1,
+            ColorType::GreyAlpha => 2,
+            ColorType::Rgb => 3,
+            _ => 4,
+        };
+        let opaque = if wide { u16::MAX } else { u8::MAX.into() };
+        let mut counts = HashMap::<[u16; 4], u64>::new();
+        for pixel in samples.chunks_exact(channels * if wide { 2 } else { 1 }) {
+            let sample = |i: usize| {
+                if wide {
+                    u16::from_be_bytes([pixel[i * 2], pixel[i * 2 + 1]])
+                } else {
+                    pixel[i].into()
+                }
+            };
+            let color = match channels {
+                1 => [sample(0), sample(0), sample(0), opaque],
+                2 => [sample(0), sample(0), sample(0), sample(1)],
+                3 => [sample(0), sample(1), sample(2), opaque],
+                _ => [sample(0), sample(1), sample(2), sample(3)],
+            };
+            *counts.entry(color).or_default() += 1;
+        }
+        // Most frequent first (ties in color order, to be deterministic)
+        let mut colors: Vec<_> = counts.into_iter().collect();
+        colors.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        colors.truncate(max_colors.into());
+        let counts: Vec<_> = colors.iter().map(|(_, count)| *count).collect();
+        let entries = colors
+            .iter()
+            .zip(frequencies(&counts))
+            .map(
+                |(([red, green, blue, alpha], _), frequency)| SuggestedColor {
+                    red: *red,
+                    green: *green,
+                    blue: *blue,
+                    alpha: *alpha,
+                    frequency,
+                },
+            )
+            .collect::<Vec<_>>();
+        self.metadata.suggested_palettes.push(SuggestedPalette {
+            name: format!("{} colors", entries.len()),
+            sample_depth: if wide { 16 } else { 8 },
+            entries,
+        });
+    }
+
+    /// Drop the unknown chunks that may not be copied.
+    fn drop_unknown(&mut self) {
+        let copy_unsafe = self.copy_unsafe;
+        self.metadata.unknown_chunks.retain(|(_, chunk)| {
+            !chunk.is_critical() && (copy_unsafe || chunk.is_safe_to_copy())
+        });
+    }
+
+    fn encode_still<R: AsRaster>(&mut self, raster: &R) -> Result<()> {
+        self.suggest(raster);
+        self.drop_unknown();
+        let image_header = header(&self.encoder, raster, None, &self.grays())?;
 
         encode(
-            &mut self.encoder.enc,
-            raster.get_u8_slice(),
+            &mut self.encoder,
             &image_header,
             raster.get_palette_colors(),
             raster.get_palette_alphas(),
-        )
+            self.transparency.as_ref(),
+            &self.metadata,
+            None,
+        )?;
+        let data = compress(&mut self.encoder, raster, &image_header);
+        self.encoder
+            .encode(&mut Chunk::ImageData(ImageData::with_data(data)))?;
+        copied(
+            &mut self.encoder,
+            &self.metadata,
+            ChunkPosition::AfterImageData,
+        )?;
+        self.encoder.encode(&mut Chunk::ImageEnd(ImageEnd))
     }
 
     /// Encode one [`Step`](struct.Step.html) of an animation.
+    ///
+    /// Without [`animate`](#method.animate), this encodes a still PNG.
+    ///
+    /// The frame region is taken from the step's frame control if set (which
+    /// must cover the whole image for the first frame), otherwise it covers
+    /// the whole image.  The raster may either be the size of the frame
+    /// region, or the size of the image (as returned by `Steps`).  Full-size
+    /// rasters are what the animation should look like after the frame is
+    /// displayed: the frame region is cropped out of them, and
+    /// `BlendOp::Over` is replaced with `BlendOp::Source` if needed.
     pub fn encode(&mut self, frame: &Step) -> Result<()> {
-        self.still(&frame.raster)
+        if self.finished {
+            return Err(EncoderError::Finished);
+        }
+        let Some(animation) = self.animation else {
+            if self.frames != 0 {
+                return Err(EncoderError::FrameCount);
+            }
+            self.frames = 1;
+            return self.still(&frame.raster);
+        };
+        if self.frames == animation.num_frames {
+            return Err(EncoderError::FrameCount);
+        }
+
+        let scaled = self.scaled(&frame.raster)?;
+        let raster = scaled.as_ref().unwrap_or(&frame.raster);
+        // Later frames may need more bits than the first, so the bit depth
+        // isn't lowered automatically for animations
+        let bit_depth = match self.header {
+            Some((ref header, _)) => header.bit_depth,
+            None => self.encoder.enc.bit_depth().unwrap_or(8),
+        };
+        let raster_header =
+            header(&self.encoder, raster, Some(bit_depth), &self.grays())?;
+        let header = match self.header {
+            None => raster_header,
+            Some((header, _)) => header,
+        };
+        let frame_control = match frame.frame {
+            Some(frame_control) => frame_control,
+            None => {
+                let delay_num =
+                    u64::from(frame.delay) * u64::from(self.delay_den);
+                let delay_num = (delay_num + 500) / 1000;
+                FrameControl {
+                    sequence: 0,
+                    width: header.width,
+                    height: header.height,
+                    x_offset: 0,
+                    y_offset: 0,
+                    delay_num: delay_num
+                        .try_into()
+                        .map_err(|_| EncoderError::FrameDelay(frame.delay))?,
+                    delay_den: self.delay_den,
+                    dispose_op: self.dispose_op,
+                    blend_op: self.blend_op,
+                }
+            }
+        };
+        if !frame_control.fits(&header)
+            || (self.frames == 0 && !frame_control.is_full(&header))
+        {
+            return Err(EncoderError::FrameRegion);
+        }
+
+        let palette = raster.get_palette_colors();
+        match self.header {
+            None => {
+                self.suggest(raster);
+                self.drop_unknown();
+                encode(
+                    &mut self.encoder,
+                    &header,
+                    palette,
+                    raster.get_palette_alphas(),
+                    self.transparency.as_ref(),
+                    &self.metadata,
+                    Some(animation),
+                )?;
+                self.header = Some((header, palette.to_vec()));
+            }
+            Some((ref header, ref colors)) => {
+                if header.color_type != raster_header.color_type
+                    || header.bit_depth != raster_header.bit_depth
+                    || colors.as_slice() != palette
+                {
+                    return Err(EncoderError::FrameFormat);
+                }
+            }
+        }
+
+        if let Some(over) = self.optimize {
+            if (raster_header.width, raster_header.height)
+                != (header.width, header.height)
+            {
+                return Err(EncoderError::FrameRegion);
+            }
+            return self.optimized(frame_control, raster, over);
+        }
+
+        // Full-size rasters are what the canvas should look like after the
+        // frame is drawn, so the frame region is cropped out of them
+        let canvas = self.canvas.get_or_insert_with(|| {
+            Canvas::new(header.width, header.height, header.bit_depth == 16)
+        });
+        let cropped;
+        let (frame_control, raster) =
+            if (raster_header.width, raster_header.height)
+                == (header.width, header.height)
+            {
+                let (frame_control, raster) =
+                    canvas.frame(frame_control, raster);
+                cropped = raster;
+                (frame_control, &cropped)
+            } else if (raster_header.width, raster_header.height)
+                == (frame_control.width, frame_control.height)
+            {
+                (frame_control, raster)
+            } else {
+                return Err(EncoderError::FrameRegion);
+            };
+        canvas.draw(&frame_control, raster);
+
+        let data = compress(&mut self.encoder, raster, &header);
+        self.write(frame_control, data)?;
+        self.frames += 1;
+        Ok(())
+    }
+
+    /// Encode a full-size frame with the changed region, and dispose and blend
+    /// operations chosen by the encoder.
+    fn optimized(
+        &mut self,
+        timing: FrameControl,
+        raster: &PngRaster,
+        over: bool,
+    ) -> Result<()> {
+        let header = self.header.as_ref().unwrap().0;
+        let canvas = self.canvas.get_or_insert_with(|| {
+            Canvas::new(header.width, header.height, header.bit_depth == 16)
+        });
+        // Drawn with `DisposeOp::Previous` so any disposal can be chosen later
+        let frame_control = FrameControl {
+            width: header.width,
+            height: header.height,
+            x_offset: 0,
+            y_offset: 0,
+            dispose_op: DisposeOp::Previous,
+            blend_op: BlendOp::Source,
+            ..timing
+        };
+
+        let Some((mut pending, pending_data)) = self.pending.take() else {
+            // First frame
+            let data = compress(&mut self.encoder, raster, &header);
+            canvas.draw(&frame_control, raster);
+            self.pending = Some((frame_control, data));
+            self.frames += 1;
+            return Ok(());
+        };
+
+        // `DisposeOp::Previous` is treated as `DisposeOp::Background` for the
+        // first frame
+        let dispose_ops: &[DisposeOp] = if self.written == 0 {
+            &[DisposeOp::None, DisposeOp::Background]
+        } else {
+            &[DisposeOp::None, DisposeOp::Background, DisposeOp::Previous]
+        };
+        let blend_ops: &[BlendOp] = if over {
+            &[BlendOp::Source, BlendOp::Over]
+        } else {
+            &[BlendOp::Source]
+        };
+        let mut best: Option<(_, Canvas, _, PngRaster, Vec<u8>)> = None;
+        for &dispose_op in dispose_ops {
+            let mut trial = canvas.clone();
+            trial.redispose(dispose_op);
+            let (x_offset, y_offset, width, height) = trial.changes(raster);
+            for &blend_op in blend_ops {
+                let (frame_control, region) = trial.frame(
+                    FrameControl {
+                        width,
+                        height,
+                        x_offset,
+                        y_offset,
+                        blend_op,
+                        ..frame_control
+                    },
+                    raster,
+                );
+                let data = compress(&mut self.encoder, &region, &header);
+                if best.as_ref().map_or(true, |best| data.len() < best.4.len())
+                {
+                    best = Some((
+                        dispose_op,
+                        trial.clone(),
+                        frame_control,
+                        region,
+                        data,
+                    ));
+                }
+            }
+        }
+        let (dispose_op, mut trial, frame_control, region, data) =
+            best.unwrap();
+        trial.draw(&frame_control, &region);
+        *canvas = trial;
+
+        pending.dispose_op = dispose_op;
+        self.write(pending, pending_data)?;
+        self.pending = Some((frame_control, data));
+        self.frames += 1;
+        Ok(())
+    }
+
+    /// Write the frame control and image data of a frame.
+    fn write(
+        &mut self,
+        frame_control: FrameControl,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        self.encoder
+            .encode(&mut Chunk::FrameControl(frame_control))?;
+        if self.written == 0 {
+            self.encoder
+                .encode(&mut Chunk::ImageData(ImageData::with_data(data)))?;
+        } else {
+            self.encoder.encode(&mut Chunk::FrameData(FrameData {
+                sequence: 0,
+                data,
+            }))?;
+        }
+        self.written += 1;
+        Ok(())
+    }
+
+    /// Finish encoding an animation, after all of it's frames have been
+    /// encoded.  Does nothing else for still PNGs.  Encoding or finishing
+    /// again afterwards is an `Error::Finished`.
+    pub fn finish(&mut self) -> Result<()> {
+        if self.finished {
+            return Err(EncoderError::Finished);
+        }
+        let Some(animation) = self.animation else {
+            self.finished = true;
+            return Ok(());
+        };
+        if self.frames != animation.num_frames {
+            return Err(EncoderError::FrameCount);
+        }
+        if let Some((mut frame_control, data)) = self.pending.take() {
+            frame_control.dispose_op = DisposeOp::None;
+            self.write(frame_control, data)?;
+        }
+        copied(
+            &mut self.encoder,
+            &self.metadata,
+            ChunkPosition::AfterImageData,
+        )?;
+        self.encoder.encode(&mut Chunk::ImageEnd(ImageEnd))?;
+        self.finished = true;
+        Ok(())
     }
 }
 
+/// Encode the chunks that come before the image data.
 pub(super) fn encode<W: Write>(
-    enc: &mut Enc<W>,
-    image: &[u8],
+    enc: &mut ChunkEnc<W>,
     header: &ImageHeader,
     palette: &[SRgb8],
     transparency: &[u8],
+    key: Option<&Transparency>,
+    metadata: &PngMetadata,
+    animation: Option<AnimationControl>,
 ) -> Result<()> {
-    enc.raw(&crate::consts::PNG_SIGNATURE)?;
-
     let transparency = Transparency::Palette(transparency.to_vec());
 
     if header.color_type == ColorType::Palette
@@ -168,87 +908,331 @@ pub(super) fn encode<W: Write>(
         .check_png_color_validity(header.bit_depth)
         .unwrap();
 
-    let data = pre_process_scanlines(
-        image,
-        header,
-        enc.filter_strategy(),
-        enc.level(),
-    );
-
-    header.write(enc)?;
+    enc.encode(&mut Chunk::ImageHeader(*header))?;
 
+    if let Some(animation) = animation {
+        enc.encode(&mut Chunk::AnimationControl(animation))?;
+    }
+    if let Some(gamma) = metadata.gamma {
+        enc.encode(&mut Chunk::Gamma(gamma))?;
+    }
+    if let Some(chromaticities) = metadata.chromaticities {
+        enc.encode(&mut Chunk::Chromaticities(chromaticities))?;
+    }
+    if let Some(srgb) = metadata.srgb {
+        enc.encode(&mut Chunk::SRgb(srgb))?;
+    }
+    if let Some(ref color_profile) = metadata.color_profile {
+        enc.encode(&mut Chunk::ColorProfile(color_profile.clone()))?;
+    }
+    if let Some(code_points) = metadata.code_points {
+        enc.encode(&mut Chunk::CodePoints(code_points))?;
+    }
+    if let Some(mastering_display) = metadata.mastering_display {
+        enc.encode(&mut Chunk::MasteringDisplay(mastering_display))?;
+    }
+    if let Some(light_level) = metadata.light_level {
+        enc.encode(&mut Chunk::ContentLightLevel(light_level))?;
+    }
+    if let Some(ref exif) = metadata.exif {
+        enc.encode(&mut Chunk::Exif(exif.clone()))?;
+    }
+    if let Some(mut significant_bits) = metadata.significant_bits {
+        // Gray rasters with a lowered bit depth are exact at that bit depth
+        if let SignificantBits::Gray(ref mut gray) = significant_bits {
+            if header.color_type == ColorType::Grey && header.bit_depth < 8 {
+                *gray = (*gray).min(header.bit_depth);
+            }
+        }
+        if !significant_bits.is_valid(header.color_type, header.bit_depth) {
+            return Err(EncoderError::ChunkValue(consts::SIGNIFICANT_BITS));
+        }
+        enc.encode(&mut Chunk::SignificantBits(significant_bits))?;
+    }
+    for (i, suggested) in metadata.suggested_palettes.iter().enumerate() {
+        let palettes = &metadata.suggested_palettes[..i];
+        if palettes.iter().any(|p| p.name == suggested.name) {
+            return Err(EncoderError::ChunkValue(consts::SUGGESTED_PALETTE));
+        }
+        enc.encode(&mut Chunk::SuggestedPalette(suggested.clone()))?;
+    }
+    copied(enc, metadata, ChunkPosition::BeforePalette)?;
     if header.color_type == ColorType::Palette {
         let palette = PaletteChunk {
             palette: palette.to_vec(),
         };
 
-        palette.write(enc)?;
+        enc.encode(&mut Chunk::Palette(palette))?;
     }
     if header.color_type == ColorType::Palette && transparency.len() != 0 {
-        transparency.write(enc)?;
+        enc.encode(&mut Chunk::Transparency(transparency))?;
     }
-    // FIXME: Transparency KEY
-    /*if color_type == ColorType::Grey
-        && discriminant(transparency)
-            == discriminant(&Transparency::GrayKey(SRgb16::default()))
-    {
-        transparency.write(&mut outv)?;
+    if let Some(ref histogram) = metadata.histogram {
+        if header.color_type != ColorType::Palette
+            || histogram.frequencies.len() != palette.len()
+        {
+            return Err(EncoderError::ChunkValue(consts::PALETTE_HISTOGRAM));
+        }
+        enc.encode(&mut Chunk::PaletteHistogram(histogram.clone()))?;
+    }
+    let max = u16::MAX >> (16 - header.bit_depth);
+    if let Some(key) = key {
+        let key = match (key, header.color_type) {
+            (Transparency::GrayKey(gray), ColorType::Grey)
+                if header.bit_depth < 8 =>
+            {
+                Transparency::GrayKey(gray / u16::from(scale(header.bit_depth)))
+            }
+            (Transparency::GrayKey(_), ColorType::Grey)
+            | (Transparency::RgbKey(..), ColorType::Rgb) => key.clone(),
+            _ => return Err(EncoderError::TransparencyKey),
+        };
+        let fits = match key {
+            Transparency::GrayKey(gray) => gray <= max,
+            Transparency::RgbKey(red, green, blue) => {
+                red <= max && green <= max && blue <= max
+            }
+            Transparency::Palette(_) => false,
+        };
+        if !fits {
+            return Err(EncoderError::TransparencyKey);
+        }
+        enc.encode(&mut Chunk::Transparency(key))?;
+    }
+    if let Some(background) = metadata.background {
+        let background = match (background, header.color_type) {
+            (Background::Gray(gray), ColorType::Grey)
+                if header.bit_depth < 8 =>
+            {
+                Background::Gray(gray / u16::from(scale(header.bit_depth)))
+            }
+            (Background::Palette(index), ColorType::Palette)
+                if usize::from(index) < palette.len() =>
+            {
+                background
+            }
+            (Background::Gray(_), ColorType::Grey | ColorType::GreyAlpha)
+            | (Background::Rgb(..), ColorType::Rgb | ColorType::Rgba) => {
+                background
+            }
+            _ => return Err(EncoderError::Background),
+        };
+        let fits = match background {
+            Background::Palette(_) => true,
+            Background::Gray(gray) => gray <= max,
+            Background::Rgb(red, green, blue) => {
+                red <= max && green <= max && blue <= max
+            }
+        };
+        if !fits {
+            return Err(EncoderError::Background);
+        }
+        enc.encode(&mut Chunk::Background(background))?;
+    }
+    if let Some(physical) = metadata.physical {
+        enc.encode(&mut Chunk::Physical(physical))?;
+    }
+    if let Some(offset) = metadata.offset {
+        enc.encode(&mut Chunk::ImageOffset(offset))?;
+    }
+    if let Some(ref calibration) = metadata.calibration {
+        enc.encode(&mut Chunk::PixelCalibration(calibration.clone()))?;
     }
-    if color_type == ColorType::Rgb
-        && discriminant(transparency)
-            == discriminant(&Transparency::RgbKey(0, 0, 0))
+    if let Some(subject_physical) = metadata.subject_physical {
+        enc.encode(&mut Chunk::SubjectPhysical(subject_physical))?;
+    }
+    if let Some(stereo) = metadata.stereo {
+        enc.encode(&mut Chunk::Stereo(stereo))?;
+    }
+    if let Some(time) = metadata.time {
+        enc.encode(&mut Chunk::Time(time))?;
+    }
+    for entry in metadata.text.iter() {
+        enc.encode(&mut text(entry))?;
+    }
+    for ext in metadata.gif_graphic_controls.iter() {
+        enc.encode(&mut Chunk::GifGraphicControlExt(*ext))?;
+    }
+    for ext in metadata.gif_applications.iter() {
+        enc.encode(&mut Chunk::GifApplicationExt(ext.clone()))?;
+    }
+    copied(enc, metadata, ChunkPosition::BeforeImageData)
+}
+
+/// Encode the unknown and custom chunks in a region.
+fn copied<W: Write>(
+    enc: &mut ChunkEnc<W>,
+    metadata: &PngMetadata,
+    position: ChunkPosition,
+) -> Result<()> {
+    for (pos, chunk) in metadata.unknown_chunks.iter() {
+        if *pos == position {
+            enc.encode(&mut Chunk::Unknown(chunk.clone()))?;
+        }
+    }
+    for (pos, chunk) in metadata.custom_chunks.iter() {
+        if *pos == position {
+            enc.encode(&mut Chunk::Custom(chunk.clone()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Get the image header for a raster, lowering the bit depth of palette and
+/// 8-bit gray rasters to `bit_depth` (or the encoder's setting if `None`).
+/// `grays` are other gray values (tRNS key and bKGD) that must fit.
+fn header<W: Write, R: AsRaster>(
+    enc: &ChunkEnc<W>,
+    raster: &R,
+    bit_depth: Option<u8>,
+    grays: &[u16],
+) -> Result<ImageHeader> {
+    let mut header = raster.get_header(enc.enc.interlace());
+    if !matches!(header.color_type, ColorType::Grey | ColorType::Palette)
+        || header.bit_depth != 8
     {
-        transparency.write(&mut outv)?;
-    }*/
-    // FIXME
-    /*if let Some(ref background) = background {
-        background.write(&mut outv, color_type)?;
-    }
-    if let Some(ref physical) = info.phys {
-        Physical::write(&mut outv, physical)?;
-    }*/
-    /*if let Some(_chunks) = info.unknown_chunks_data(ChunkPosition::PLTE) {
-        // add_unknown_chunks(&mut outv, _chunks);
-    }*/
-    ImageData::with_data(data).write(enc)?;
-    /*if let Some(ref time) = info.time {
-        time.write(&mut outv)?;
-    }*/
-    // FIXME: Text
-    /*for ntext in info.text.iter() {
-        if ntext.key.len() > 79 || ntext.key.is_empty() {
-            return Err(EncoderError::KeySize(ntext.key.len()));
-        }
-        Text {
-            key: ntext.key.clone(),
-            val: ntext.val.clone(),
-        }
-        .write(&mut outv)?;
-    }
-    for ztext in info.ztext.iter() {
-        if ztext.key.len() > 79 || ztext.key.is_empty() {
-            return Err(EncoderError::KeySize(ztext.key.len()));
-        }
-        ZText {
-            key: ztext.key.clone(),
-            val: ztext.val.clone(),
-        }
-        .write(&mut outv, zlib_compression)?;
-    }
-    for chunk in info.itext.iter() {
-        if chunk.key.len() > 79 || chunk.key.is_empty() {
-            return Err(EncoderError::KeySize(chunk.key.len()));
-        }
-        chunk.write(
-            &mut outv,
-            text_compression,
-            zlib_compression,
-        )?;
-    }*/
-    /*if let Some(_chunks) = info.unknown_chunks_data(ChunkPosition::IDAT) {
-        // add_unknown_chunks(&mut outv, _chunks);
-    }*/
-    ImageEnd.write(enc)
+        return Ok(header);
+    }
+
+    let samples = raster.get_u8_slice();
+    let fits = |bit_depth: u8| match header.color_type {
+        ColorType::Palette => {
+            let max = 1 << bit_depth;
+            raster.get_palette_colors().len() <= max
+                && samples.iter().all(|index| usize::from(*index) < max)
+        }
+        _ => {
+            let scale = scale(bit_depth);
+            grays.iter().all(|gray| {
+                *gray <= u8::MAX.into() && gray % u16::from(scale) == 0
+            }) && samples.iter().all(|value| value % scale == 0)
+        }
+    };
+    header.bit_depth = match bit_depth.or(enc.enc.bit_depth()) {
+        Some(bit_depth) if fits(bit_depth) => bit_depth,
+        Some(bit_depth) => return Err(EncoderError::BitDepth(bit_depth)),
+        None => [1, 2, 4, 8].into_iter().find(|d| fits(*d)).unwrap_or(8),
+    };
+    Ok(header)
+}
+
+/// Filter and compress the pixel data of a frame, with the color type and bit
+/// depth of the image header.
+fn compress<W: Write, R: AsRaster>(
+    enc: &mut ChunkEnc<W>,
+    raster: &R,
+    header: &ImageHeader,
+) -> Vec<u8> {
+    let raster_header = raster.get_header(header.interlace);
+    let header = ImageHeader {
+        width: raster_header.width,
+        height: raster_header.height,
+        ..*header
+    };
+    let samples = raster.get_u8_slice();
+    let packed;
+    let samples = if header.bit_depth < 8 {
+        packed = pack(samples, &header);
+        packed.as_slice()
+    } else {
+        samples
+    };
+    let data = pre_process_scanlines(
+        samples,
+        &header,
+        enc.enc.filter_strategy(),
+        enc.enc.level(),
+    );
+    let mut zlib = Vec::new();
+    zlib::compress(&mut zlib, data.as_slice(), enc.enc.level());
+    zlib
+}
+
+/// Get the text chunk for a text entry, promoting text that can't be
+/// represented in Latin-1 to iTXt.
+fn text(entry: &TextEntry) -> Chunk {
+    let latin1 = entry.text.chars().all(|c| u32::from(c) <= 0xFF);
+    if entry.langtag.is_some() || entry.transkey.is_some() || !latin1 {
+        return Chunk::InternationalText(InternationalText {
+            key: entry.key.clone(),
+            langtag: entry.langtag.clone().unwrap_or_default(),
+            transkey: entry.transkey.clone().unwrap_or_default(),
+            val: entry.text.clone(),
+            compressed: entry.compressed,
+        });
+    }
+    if entry.compressed {
+        return Chunk::CompressedText(CompressedText {
+            key: entry.key.clone(),
+            val: entry.text.clone(),
+        });
+    }
+    Chunk::Text(Text {
+        key: entry.key.clone(),
+        val: entry.text.clone(),
+    })
+}
+
+/// Scale pixel counts to frequencies, with the most frequent at 65535 (and
+/// used colors at least 1).
+fn frequencies(counts: &[u64]) -> Vec<u16> {
+    let most = counts.iter().copied().max().unwrap_or(0).max(1);
+    counts
+        .iter()
+        .map(|count| {
+            let frequency = (count * u64::from(u16::MAX) + most / 2) / most;
+            (frequency as u16).max((*count > 0).into())
+        })
+        .collect()
+}
+
+/// Scale samples up from their significant bits to the full range of the
+/// raster (rounded to the nearest value).
+fn scale_up<R: AsRaster>(
+    raster: &R,
+    significant_bits: SignificantBits,
+) -> Result<PngRaster> {
+    let mut raster = raster.to_png_raster();
+    let mut error = None;
+    raster.map_samples(|channel, value, bits| {
+        let Some(significant) = significant_bits.bits(channel) else {
+            return value;
+        };
+        if significant == 0 || significant > bits || value >> significant != 0 {
+            error = Some(EncoderError::BitDepth(significant));
+            return value;
+        }
+        let max = u32::MAX >> (32 - bits);
+        let significant_max = u32::MAX >> (32 - significant);
+        let scaled =
+            (u32::from(value) * max + significant_max / 2) / significant_max;
+        scaled as u16
+    });
+    match error {
+        Some(error) => Err(error),
+        None => Ok(raster),
+    }
+}
+
+/// Get the factor between 8-bit gray values and gray values of a bit depth.
+fn scale(bit_depth: u8) -> u8 {
+    u8::MAX / (u8::MAX >> (8 - bit_depth))
+}
+
+/// Pack 8-bit palette indices or gray values into 1, 2 or 4-bit samples
+/// (without padding bits between rows).
+fn pack(samples: &[u8], header: &ImageHeader) -> Vec<u8> {
+    let bits = usize::from(header.bit_depth);
+    let scale = match header.color_type {
+        ColorType::Palette => 1,
+        _ => scale(header.bit_depth),
+    };
+    let mut packed = vec![0; (samples.len() * bits + 7) / 8];
+    for (i, sample) in samples.iter().enumerate() {
+        let bit = i * bits;
+        packed[bit / 8] |= (sample / scale) << (8 - bits - bit % 8);
+    }
+    packed
 }
 
 /// The opposite of the remove_padding_bits function
@@ -1,6 +1,7 @@
 use std::io::Write;
 
 use crate::{
+    chunk::CustomChunk,
     consts,
     encode::{ChunkEnc, Error, FilterStrategy, Result, StepEnc},
 };
@@ -104,6 +105,16 @@ impl<W: Write> Enc<W> {
     pub(crate) fn interlace(&self) -> bool {
         self.encode.interlace
     }
+
+    /// Get the bit depth for palette and gray rasters, `None` if automatic.
+    pub(crate) fn bit_depth(&self) -> Option<u8> {
+        self.encode.bit_depth
+    }
+
+    /// Whether or not a user-defined chunk type is registered.
+    pub(crate) fn is_custom(&self, name: [u8; 4]) -> bool {
+        self.encode.custom.contains(&name)
+    }
 }
 
 /// PNG file encoder
@@ -122,6 +133,8 @@ pub struct Encoder<W: Write> {
     filter_strategy: Option<FilterStrategy>,
     level: u8,
     interlace: bool,
+    bit_depth: Option<u8>,
+    custom: Vec<[u8; 4]>,
     writer: W,
 }
 
@@ -133,6 +146,8 @@ impl<W: Write> Encoder<W> {
             filter_strategy: None,
             level: 6,
             interlace: false,
+            bit_depth: Some(8),
+            custom: Vec::new(),
         }
     }
 
@@ -157,6 +172,31 @@ impl<W: Write> Encoder<W> {
         self
     }
 
+    /// Set the bit depth of palette and 8-bit gray rasters (default: 8).  Must
+    /// be 1, 2, 4 or 8.  Encoding fails if a palette index or gray value
+    /// doesn't fit.
+    pub fn bit_depth(mut self, bit_depth: u8) -> Self {
+        assert!(matches!(bit_depth, 1 | 2 | 4 | 8));
+        self.bit_depth = Some(bit_depth);
+        self
+    }
+
+    /// Use the smallest bit depth that fits for palette and 8-bit gray rasters
+    /// (animations use 8, since later frames may need more bits than the
+    /// first).
+    pub fn auto_bit_depth(mut self) -> Self {
+        self.bit_depth = None;
+        self
+    }
+
+    /// Register a user-defined chunk type, so
+    /// [`Chunk::Custom`](chunk/enum.Chunk.html#variant.Custom) chunks of it can
+    /// be encoded.
+    pub fn custom_chunk<C: CustomChunk>(mut self) -> Self {
+        self.custom.push(C::NAME);
+        self
+    }
+
     /// Convert into a chunk encoder.
     pub fn into_chunk_enc(self) -> ChunkEnc<W> {
         ChunkEnc::new(self.into_enc())
@@ -10,21 +10,19 @@
 //!
 //! ### Example
 //! ```rust
-//! // Saving raster as a PNG file
+//! // Encoding raster as PNG data
 //! let raster = png_pong::PngRaster::Rgba8(pix::Raster::with_pixels(1, 1, &[
 //!     pix::rgb::SRgba8::new(0, 0, 0, 0)][..]
 //! ));
 //! let mut out_data = Vec::new();
 //! let mut encoder = png_pong::Encoder::new(&mut out_data).into_step_enc();
-//! let step = png_pong::Step{ raster, delay: 0 };
+//! let step = png_pong::Step{ raster, delay: 0, frame: None };
 //! encoder.encode(&step).expect("Failed to add frame");
-//! std::fs::write("graphic.png", out_data).expect("Failed to save image");
 //!
-//! // Loading PNG file into a Raster
-//! let data = std::fs::read("graphic.png").expect("Failed to open PNG");
-//! let data = std::io::Cursor::new(data);
+//! // Decoding PNG data into a Raster
+//! let data = std::io::Cursor::new(out_data);
 //! let decoder = png_pong::Decoder::new(data).expect("Not PNG").into_steps();
-//! let png_pong::Step { raster, delay } = decoder
+//! let png_pong::Step { raster, delay, .. } = decoder
 //!     .last()
 //!     .expect("No frames in PNG")
 //!     .expect("PNG parsing error");
@@ -60,13 +58,17 @@ pub(crate) mod decoder;
 
 mod adam7;
 mod bitstream;
+mod canvas;
+mod color;
 mod consts;
 mod encoder;
+mod metadata;
 mod raster;
 mod step;
 mod zlib;
 
 pub use decoder::Decoder;
 pub use encoder::Encoder;
+pub use metadata::{PngMetadata, TextEntry};
 pub use raster::PngRaster;
 pub use step::Step;
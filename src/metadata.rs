@@ -0,0 +1,83 @@
+use crate::chunk::{
+    Background, Chromaticities, ChunkPosition, CodePoints, ColorProfile,
+    ContentLightLevel, Custom, Exif, Gamma, GifApplicationExt,
+    GifGraphicControlExt, ImageOffset, MasteringDisplay, PaletteHistogram,
+    Physical, PixelCalibration, SRgb, SignificantBits, Stereo, SubjectPhysical,
+    SuggestedPalette, Time, Unknown,
+};
+
+/// A text entry from a tEXt, zTXt or iTXt chunk.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TextEntry {
+    /// A keyword that gives a short description of what the text represents,
+    /// e.g. Title, Author, Description, or anything else.
+    pub key: String,
+    /// The actual message.
+    pub text: String,
+    /// Language tag (iTXt only, `None` for tEXt and zTXt).
+    pub langtag: Option<String>,
+    /// Translated keyword (iTXt only, `None` for tEXt and zTXt).
+    pub transkey: Option<String>,
+    /// Whether or not the text was compressed (zTXt, or compressed iTXt).
+    pub compressed: bool,
+}
+
+/// Ancillary metadata of a PNG file.
+///
+/// The color space of the pixels is described by `gamma` and
+/// `chromaticities`, overridden by `srgb`, `color_profile` or `code_points` if
+/// present (in increasing order of precedence).
+#[derive(Clone, Debug, Default)]
+pub struct PngMetadata {
+    /// Text entries, in the order they appear in the file.
+    pub text: Vec<TextEntry>,
+    /// Last modification time (tIME)
+    pub time: Option<Time>,
+    /// Physical pixel dimensions (pHYs)
+    pub physical: Option<Physical>,
+    /// Suggested background color (bKGD).  Gray values of 1, 2 and 4-bit
+    /// images are scaled up to 8 bits, like the raster.
+    pub background: Option<Background>,
+    /// Image gamma (gAMA)
+    pub gamma: Option<Gamma>,
+    /// Primary chromaticities and white point (cHRM)
+    pub chromaticities: Option<Chromaticities>,
+    /// Standard RGB color space (sRGB)
+    pub srgb: Option<SRgb>,
+    /// Embedded ICC profile (iCCP)
+    pub color_profile: Option<ColorProfile>,
+    /// Coding-independent code points (cICP)
+    pub code_points: Option<CodePoints>,
+    /// Mastering display color volume (mDCv)
+    pub mastering_display: Option<MasteringDisplay>,
+    /// Content light level (cLLi)
+    pub light_level: Option<ContentLightLevel>,
+    /// EXIF data (eXIf)
+    pub exif: Option<Exif>,
+    /// Significant bits of each channel (sBIT)
+    pub significant_bits: Option<SignificantBits>,
+    /// Suggested palettes, in the order they appear in the file (sPLT)
+    pub suggested_palettes: Vec<SuggestedPalette>,
+    /// Frequencies of the palette entries (hIST)
+    pub histogram: Option<PaletteHistogram>,
+    /// Position of the image on a page (oFFs)
+    pub offset: Option<ImageOffset>,
+    /// Mapping of sample values to physical values (pCAL)
+    pub calibration: Option<PixelCalibration>,
+    /// Physical size of the subject covered by each pixel (sCAL)
+    pub subject_physical: Option<SubjectPhysical>,
+    /// Stereo image layout (sTER)
+    pub stereo: Option<Stereo>,
+    /// GIF graphic control extensions, in the order they appear in the file
+    /// (gIFg)
+    pub gif_graphic_controls: Vec<GifGraphicControlExt>,
+    /// GIF application extensions, in the order they appear in the file
+    /// (gIFx)
+    pub gif_applications: Vec<GifApplicationExt>,
+    /// Unknown ancillary chunks, with the region they appear in, in the order
+    /// they appear in the file
+    pub unknown_chunks: Vec<(ChunkPosition, Unknown)>,
+    /// Chunks of custom types registered on the `Decoder`, with the region
+    /// they appear in, in the order they appear in the file
+    pub custom_chunks: Vec<(ChunkPosition, Custom)>,
+}
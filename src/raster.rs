@@ -2,15 +2,16 @@ use pix::{
     chan::{Ch16, Ch8},
     el::Pixel,
     gray::{Gray8, SGray16, SGray8, SGraya16, SGraya8},
-    rgb::{SRgb16, SRgb8, SRgba16, SRgba8},
-    Palette, Raster,
+    rgb::{Rgb, SRgb16, SRgb8, SRgba16, SRgba8},
+    Palette, Raster, Region,
 };
 
-use crate::chunk::{ColorType, ImageHeader};
+use crate::chunk::{ColorType, ImageHeader, Orientation};
 
 /// A Raster of one of the PNG types (all are sRGB gamma).
 /// PNGs with less than 8 bits per channel are scaled up to 8 bits per channel.
 #[allow(missing_debug_implementations)]
+#[derive(Clone)]
 pub enum PngRaster {
     /// 1, 2, 4, 8-bit greyscale
     Gray8(Raster<SGray8>),
@@ -101,6 +102,139 @@ impl PngRaster {
             },
         }
     }
+
+    /// Copy a region out of the raster.
+    pub(crate) fn crop(&self, region: Region) -> PngRaster {
+        fn crop<P: Pixel>(raster: &Raster<P>, region: Region) -> Raster<P> {
+            let mut out = Raster::with_clear(region.width(), region.height());
+            out.copy_raster((), raster, region);
+            out
+        }
+
+        use PngRaster::*;
+        match self {
+            Gray8(r) => Gray8(crop(r, region)),
+            Gray16(r) => Gray16(crop(r, region)),
+            Rgb8(r) => Rgb8(crop(r, region)),
+            Rgb16(r) => Rgb16(crop(r, region)),
+            Palette(r, p, a) => Palette(crop(r, region), p.clone(), a.clone()),
+            Graya8(r) => Graya8(crop(r, region)),
+            Graya16(r) => Graya16(crop(r, region)),
+            Rgba8(r) => Rgba8(crop(r, region)),
+            Rgba16(r) => Rgba16(crop(r, region)),
+        }
+    }
+
+    /// Replace each sample with `f(channel, value, bits)`, where `channel` is
+    /// 0 to 2 for red, green and blue (0 for gray), 3 for alpha, and `bits`
+    /// is 8 or 16.  Palette colors are replaced instead of the indices.
+    pub(crate) fn map_samples(
+        &mut self,
+        mut f: impl FnMut(usize, u16, u8) -> u16,
+    ) {
+        /// Map 8-bit samples, with channel numbers of each sample in a pixel.
+        fn map8(
+            samples: &mut [u8],
+            channels: &[usize],
+            f: &mut impl FnMut(usize, u16, u8) -> u16,
+        ) {
+            for pixel in samples.chunks_exact_mut(channels.len()) {
+                for (sample, channel) in pixel.iter_mut().zip(channels) {
+                    *sample = f(*channel, (*sample).into(), 8) as u8;
+                }
+            }
+        }
+
+        /// Map big endian 16-bit samples, with channel numbers of each
+        /// sample in a pixel.
+        fn map16(
+            samples: &mut [u8],
+            channels: &[usize],
+            f: &mut impl FnMut(usize, u16, u8) -> u16,
+        ) {
+            for pixel in samples.chunks_exact_mut(channels.len() * 2) {
+                for (sample, channel) in pixel.chunks_exact_mut(2).zip(channels)
+                {
+                    let value = u16::from_be_bytes([sample[0], sample[1]]);
+                    sample
+                        .copy_from_slice(&f(*channel, value, 16).to_be_bytes());
+                }
+            }
+        }
+
+        use PngRaster::*;
+        let f = &mut f;
+        match self {
+            Gray8(r) => map8(r.as_u8_slice_mut(), &[0], f),
+            Gray16(r) => map16(r.as_u8_slice_mut(), &[0], f),
+            Graya8(r) => map8(r.as_u8_slice_mut(), &[0, 3], f),
+            Graya16(r) => map16(r.as_u8_slice_mut(), &[0, 3], f),
+            Rgb8(r) => map8(r.as_u8_slice_mut(), &[0, 1, 2], f),
+            Rgb16(r) => map16(r.as_u8_slice_mut(), &[0, 1, 2], f),
+            Rgba8(r) => map8(r.as_u8_slice_mut(), &[0, 1, 2, 3], f),
+            Rgba16(r) => map16(r.as_u8_slice_mut(), &[0, 1, 2, 3], f),
+            Palette(_, palette, _) => {
+                for i in 0..palette.colors().len() {
+                    let color = palette.colors()[i];
+                    let mut rgb = [
+                        u8::from(Rgb::red(color)),
+                        u8::from(Rgb::green(color)),
+                        u8::from(Rgb::blue(color)),
+                    ];
+                    map8(&mut rgb, &[0, 1, 2], f);
+                    palette
+                        .replace_entry(i, SRgb8::new(rgb[0], rgb[1], rgb[2]));
+                }
+            }
+        }
+    }
+
+    /// Rotate and flip the raster so that it displays upright.
+    pub(crate) fn orient(&self, orientation: Orientation) -> PngRaster {
+        fn orient<P: Pixel>(
+            raster: &Raster<P>,
+            orientation: Orientation,
+        ) -> Raster<P> {
+            use Orientation::*;
+            let (w, h) = (raster.width() as i32, raster.height() as i32);
+            let (width, height) = match orientation {
+                Normal | FlipHorizontal | Rotate180 | FlipVertical => (w, h),
+                Transpose | Rotate90 | Transverse | Rotate270 => (h, w),
+            };
+            let mut pixels = Vec::with_capacity(raster.pixels().len());
+            for y in 0..height {
+                for x in 0..width {
+                    let (x, y) = match orientation {
+                        Normal => (x, y),
+                        FlipHorizontal => (w - 1 - x, y),
+                        Rotate180 => (w - 1 - x, h - 1 - y),
+                        FlipVertical => (x, h - 1 - y),
+                        Transpose => (y, x),
+                        Rotate90 => (y, h - 1 - x),
+                        Transverse => (w - 1 - y, h - 1 - x),
+                        Rotate270 => (w - 1 - y, x),
+                    };
+                    pixels.push(raster.pixel(x, y));
+                }
+            }
+            Raster::with_pixels(width as u32, height as u32, pixels)
+        }
+
+        use PngRaster::*;
+        match self {
+            Gray8(r) => Gray8(orient(r, orientation)),
+            Gray16(r) => Gray16(orient(r, orientation)),
+            Rgb8(r) => Rgb8(orient(r, orientation)),
+            Rgb16(r) => Rgb16(orient(r, orientation)),
+            Palette(r, p, a) => {
+                Palette(orient(r, orientation), p.clone(), a.clone())
+            }
+            Graya8(r) => Graya8(orient(r, orientation)),
+            Graya16(r) => Graya16(orient(r, orientation)),
+            Rgba8(r) => Rgba8(orient(r, orientation)),
+            Rgba16(r) => Rgba16(orient(r, orientation)),
+        }
+    }
 }
 
 impl<P: Pixel> From<PngRaster> for Raster<P>
@@ -124,7 +258,7 @@ where
                         px.one(),
                         px.two(),
                         px.three(),
-                        Ch8::new(pa[i]),
+                        Ch8::new(pa.get(i).cloned().unwrap_or(u8::MAX)),
                     );
                     pixels.push(px.convert());
                 }
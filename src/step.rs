@@ -1,11 +1,16 @@
-use crate::PngRaster;
+use crate::{chunk::FrameControl, PngRaster};
 
 /// A Frame
 pub struct Step {
     /// Raster associated with this frame.
     pub raster: PngRaster,
-    /// TODO: Delay associated with this frame.
+    /// Delay associated with this frame in milliseconds.
     pub delay: u32,
+    /// APNG frame control (region, delay fraction, dispose and blend
+    /// operations) associated with this frame, `None` for still images.
+    ///
+    /// When encoding, this overrides `delay` and the `StepEnc` defaults.
+    pub frame: Option<FrameControl>,
 }
 
 impl std::fmt::Debug for Step {
@@ -0,0 +1,414 @@
+use std::io::Cursor;
+
+use png_pong::{
+    chunk::{AnimationControl, Chunk, Unknown},
+    decode::Error,
+    encode::Error as EncodeError,
+    Decoder, Encoder, PngRaster, Step,
+};
+
+fn steps(filename: &str) -> Vec<Step> {
+    let file = std::fs::read(filename).unwrap();
+    Decoder::new(Cursor::new(file))
+        .expect("Not PNG")
+        .into_steps()
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+fn pixels(raster: &PngRaster) -> &[u8] {
+    match raster {
+        PngRaster::Rgba8(r) => r.as_u8_slice(),
+        PngRaster::Rgba16(r) => r.as_u8_slice(),
+        _ => panic!("Animation frames should be RGBA"),
+    }
+}
+
+fn size(raster: &PngRaster) -> (u32, u32) {
+    match raster {
+        PngRaster::Rgba8(r) => (r.width(), r.height()),
+        PngRaster::Rgba16(r) => (r.width(), r.height()),
+        _ => panic!("Animation frames should be RGBA"),
+    }
+}
+
+#[test]
+fn frame_count() {
+    for (filename, count, width, height) in [
+        ("tests/apng/APNG-Fadeout.png", 128, 256, 192),
+        ("tests/apng/APNG-Glass.png", 15, 288, 224),
+        (
+            "tests/apng/APNG-IC1696_by_Jukka_Metsavainio.png",
+            2,
+            400,
+            540,
+        ),
+        ("tests/apng/APNG-Icos4D.png", 48, 128, 128),
+        ("tests/apng/APNG-Saturn.png", 20, 251, 240),
+        ("tests/apng/APNG-StarV838.png", 4, 412, 412),
+        ("tests/apng/APNG-from-GIF-LostWorld.png", 2, 322, 477),
+        ("tests/apng/APNG-from-GIF-Mouse.png", 50, 235, 148),
+        ("tests/apng/APNG.png", 100, 192, 192),
+        ("tests/apng/Gold.png", 3, 200, 200),
+        ("tests/apng/Newton.png", 36, 240, 174),
+        ("tests/apng/clock.png", 40, 150, 150),
+        ("tests/apng/colors-apng.png", 30, 168, 104),
+        ("tests/apng/diamond-apng.png", 11, 180, 180),
+        ("tests/apng/graham11.png", 156, 403, 156),
+        ("tests/apng/o_sample.png", 6, 116, 135),
+    ] {
+        let steps = steps(filename);
+        assert_eq!(steps.len(), count, "{filename}");
+        for step in steps {
+            assert_eq!(size(&step.raster), (width, height), "{filename}");
+        }
+    }
+}
+
+#[test]
+fn frame_delay() {
+    let delays: Vec<u32> = steps("tests/apng/clock.png")
+        .iter()
+        .map(|step| step.delay)
+        .collect();
+    assert_eq!(delays, [40; 40]);
+
+    let steps = steps("tests/apng/APNG-from-GIF-Mouse.png");
+    assert_eq!(steps[0].delay, 510);
+    assert_eq!(steps[1].delay, 85);
+}
+
+#[test]
+fn still_image() {
+    let steps = steps("tests/png/fry.png");
+    assert_eq!(steps.len(), 1);
+    assert_eq!(steps[0].delay, 0);
+}
+
+#[test]
+fn stray_frames() {
+    // Without acTL, fcTL and fdAT are ignored and the file is a still image
+    let file = std::fs::read("tests/apng/clock.png").unwrap();
+    let file =
+        rewrite(&file, |chunk| !matches!(chunk, Chunk::AnimationControl(_)));
+    let steps = Decoder::new(file.as_slice())
+        .expect("Not PNG")
+        .into_steps()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(steps.len(), 1);
+    assert_eq!(steps[0].delay, 0);
+    assert!(steps[0].frame.is_none());
+}
+
+fn rewrite(file: &[u8], mut filter: impl FnMut(&mut Chunk) -> bool) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut encoder = Encoder::new(&mut out).into_chunk_enc();
+    for chunk in Decoder::new(file).expect("Not PNG").into_chunks() {
+        let mut chunk = chunk.unwrap();
+        if filter(&mut chunk) {
+            encoder.encode(&mut chunk).unwrap();
+        }
+    }
+    out
+}
+
+#[test]
+fn chunk_roundtrip() {
+    let file = std::fs::read("tests/apng/clock.png").unwrap();
+    assert_eq!(rewrite(&file, |_| true), file);
+}
+
+#[test]
+fn chunk_drop_frame() {
+    let file = std::fs::read("tests/apng/clock.png").unwrap();
+    // Drop the second frame, which is renumbered by the encoder
+    let mut frame = 0;
+    let file = rewrite(&file, |chunk| match chunk {
+        Chunk::AnimationControl(actl) => {
+            actl.num_frames -= 1;
+            true
+        }
+        Chunk::FrameControl(_) => {
+            frame += 1;
+            frame != 2
+        }
+        Chunk::FrameData(_) => frame != 2,
+        _ => true,
+    });
+    let steps = Decoder::new(file.as_slice())
+        .expect("Not PNG")
+        .into_steps()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(steps.len(), 39);
+}
+
+#[test]
+fn frame_count_mismatch() {
+    let file = std::fs::read("tests/apng/clock.png").unwrap();
+    for num_frames in [3, 39, 41] {
+        let file = rewrite(&file, |chunk| {
+            if let Chunk::AnimationControl(actl) = chunk {
+                actl.num_frames = num_frames;
+            }
+            true
+        });
+        let result = Decoder::new(file.as_slice())
+            .expect("Not PNG")
+            .into_steps()
+            .collect::<Result<Vec<_>, _>>();
+        assert!(matches!(result, Err(Error::FrameCount(40))), "{num_frames}");
+    }
+}
+
+#[test]
+fn zero_frames() {
+    let file = std::fs::read("tests/apng/clock.png").unwrap();
+
+    // Can't be encoded
+    let mut out = Vec::new();
+    let mut encoder = Encoder::new(&mut out).into_chunk_enc();
+    let mut chunks = Decoder::new(file.as_slice()).unwrap().into_chunks();
+    encoder
+        .encode(&mut chunks.next().unwrap().unwrap())
+        .unwrap();
+    assert!(matches!(
+        encoder.encode(&mut Chunk::AnimationControl(AnimationControl {
+            num_frames: 0,
+            num_plays: 0,
+        })),
+        Err(EncodeError::ChunkValue(name)) if &name == b"acTL"
+    ));
+
+    // Can't be decoded
+    let file = rewrite(&file, |chunk| {
+        if let Chunk::AnimationControl(_) = chunk {
+            *chunk = Chunk::Unknown(Unknown {
+                name: *b"acTL",
+                data: vec![0; 8],
+            });
+        }
+        true
+    });
+    let result = Decoder::new(file.as_slice())
+        .expect("Not PNG")
+        .into_steps()
+        .collect::<Result<Vec<_>, _>>();
+    assert!(matches!(
+        result,
+        Err(Error::ChunkValue(name)) if &name == b"acTL"
+    ));
+}
+
+#[test]
+fn chunk_sequence() {
+    let mut file = std::fs::read("tests/apng/clock.png").unwrap();
+    // Change the sequence number of the first fcTL chunk from 0 to 1
+    let fctl = file.windows(4).position(|name| name == b"fcTL").unwrap();
+    file[fctl + 7] = 1;
+    let error = Decoder::new(file.as_slice())
+        .expect("Not PNG")
+        .into_chunks()
+        .find_map(Result::err);
+    assert!(matches!(error, Some(Error::Sequence(1))));
+}
+
+#[test]
+fn encode_roundtrip() {
+    let steps_a = steps("tests/apng/clock.png");
+    let mut file = Vec::new();
+    let mut encoder = Encoder::new(&mut file)
+        .into_step_enc()
+        .animate(steps_a.len() as u32, 0);
+    for step in &steps_a {
+        encoder.encode(step).unwrap();
+    }
+    encoder.finish().unwrap();
+
+    let steps_b = Decoder::new(file.as_slice())
+        .expect("Not PNG")
+        .into_steps()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(steps_a.len(), steps_b.len());
+    for (a, b) in steps_a.iter().zip(steps_b.iter()) {
+        assert_eq!(a.delay, b.delay);
+        assert_eq!(pixels(&a.raster), pixels(&b.raster));
+    }
+}
+
+#[test]
+fn encode_frame_control() {
+    for filename in [
+        "tests/apng/APNG-Fadeout.png",
+        "tests/apng/APNG-Icos4D.png",
+        "tests/apng/APNG-from-GIF-Mouse.png",
+        "tests/apng/clock.png",
+        "tests/apng/graham11.png",
+    ] {
+        let steps_a = steps(filename);
+        let mut file = Vec::new();
+        let mut encoder = Encoder::new(&mut file)
+            .into_step_enc()
+            .animate(steps_a.len() as u32, 0);
+        for step in &steps_a {
+            encoder.encode(step).unwrap();
+        }
+        encoder.finish().unwrap();
+
+        let steps_b = Decoder::new(file.as_slice())
+            .expect("Not PNG")
+            .into_steps()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(steps_a.len(), steps_b.len(), "{filename}");
+        for (a, b) in steps_a.iter().zip(steps_b.iter()) {
+            // Sequence numbers depend on how frame data is split into chunks
+            let (mut a_frame, mut b_frame) =
+                (a.frame.unwrap(), b.frame.unwrap());
+            a_frame.sequence = 0;
+            b_frame.sequence = 0;
+            assert_eq!(a_frame, b_frame, "{filename}");
+            assert_eq!(pixels(&a.raster), pixels(&b.raster), "{filename}");
+        }
+    }
+}
+
+#[test]
+fn encode_frame_count() {
+    let steps = steps("tests/apng/clock.png");
+    let mut file = Vec::new();
+    let mut encoder = Encoder::new(&mut file).into_step_enc().animate(2, 0);
+    encoder.encode(&steps[0]).unwrap();
+    assert!(matches!(encoder.finish(), Err(EncodeError::FrameCount)));
+    encoder.encode(&steps[1]).unwrap();
+    assert!(matches!(
+        encoder.encode(&steps[2]),
+        Err(EncodeError::FrameCount)
+    ));
+    encoder.finish().unwrap();
+}
+
+#[test]
+fn encode_finished() {
+    let steps = steps("tests/apng/clock.png");
+    let mut file = Vec::new();
+    let mut encoder = Encoder::new(&mut file).into_step_enc().animate(1, 0);
+    encoder.encode(&steps[0]).unwrap();
+    encoder.finish().unwrap();
+    assert!(matches!(encoder.finish(), Err(EncodeError::Finished)));
+    assert!(matches!(
+        encoder.encode(&steps[1]),
+        Err(EncodeError::Finished)
+    ));
+    assert_eq!(file.windows(4).filter(|w| w == b"IEND").count(), 1);
+}
+
+#[test]
+fn encode_frame_size() {
+    let mut file = Vec::new();
+    let mut encoder = Encoder::new(&mut file).into_step_enc().animate(2, 0);
+    let frame = |width, height| Step {
+        raster: PngRaster::Rgba8(pix::Raster::with_clear(width, height)),
+        delay: 100,
+        frame: None,
+    };
+    encoder.encode(&frame(4, 4)).unwrap();
+    assert!(matches!(
+        encoder.encode(&frame(4, 5)),
+        Err(EncodeError::FrameRegion)
+    ));
+}
+
+#[test]
+fn raw_frames() {
+    let file = std::fs::read("tests/apng/clock.png").unwrap();
+    let frames = Decoder::new(file.as_slice())
+        .expect("Not PNG")
+        .into_frames()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(frames.len(), 40);
+    for frame in &frames {
+        let frame_control = frame.frame.unwrap();
+        let PngRaster::Palette(raster, _, _) = &frame.raster else {
+            panic!("Raw clock frames should be palette");
+        };
+        assert_eq!(
+            (raster.width(), raster.height()),
+            (frame_control.width, frame_control.height)
+        );
+    }
+
+    // Raw frames encode back to the same composited frames
+    let mut out = Vec::new();
+    let mut encoder = Encoder::new(&mut out).into_step_enc().animate(40, 0);
+    for frame in &frames {
+        encoder.encode(frame).unwrap();
+    }
+    encoder.finish().unwrap();
+    let steps_a = steps("tests/apng/clock.png");
+    let steps_b = Decoder::new(out.as_slice())
+        .expect("Not PNG")
+        .into_steps()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    for (a, b) in steps_a.iter().zip(steps_b.iter()) {
+        assert_eq!(pixels(&a.raster), pixels(&b.raster));
+    }
+}
+
+fn encode_steps(
+    steps: &[Step],
+    optimize: Option<bool>,
+) -> (Vec<u8>, Vec<Step>) {
+    let mut file = Vec::new();
+    let mut encoder = Encoder::new(&mut file)
+        .into_step_enc()
+        .animate(steps.len() as u32, 0);
+    if let Some(over) = optimize {
+        encoder = encoder.optimize(over);
+    }
+    for step in steps {
+        // Full canvas frames
+        let step = Step {
+            raster: PngRaster::Rgba8(match &step.raster {
+                PngRaster::Rgba8(r) => pix::Raster::with_raster(r),
+                _ => panic!("Animation frames should be Rgba8"),
+            }),
+            delay: step.delay,
+            frame: None,
+        };
+        encoder.encode(&step).unwrap();
+    }
+    encoder.finish().unwrap();
+    let decoded = Decoder::new(file.as_slice())
+        .expect("Not PNG")
+        .into_steps()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    (file, decoded)
+}
+
+#[test]
+fn encode_optimize() {
+    for filename in [
+        "tests/apng/APNG-Icos4D.png",
+        "tests/apng/APNG-from-GIF-Mouse.png",
+        "tests/apng/clock.png",
+        "tests/apng/diamond-apng.png",
+    ] {
+        let steps = steps(filename);
+        let (full, _) = encode_steps(&steps, None);
+        for over in [false, true] {
+            let (file, decoded) = encode_steps(&steps, Some(over));
+            assert!(file.len() < full.len(), "{filename}");
+            assert_eq!(steps.len(), decoded.len(), "{filename}");
+            for (a, b) in steps.iter().zip(decoded.iter()) {
+                assert_eq!(a.delay, b.delay, "{filename}");
+                assert_eq!(pixels(&a.raster), pixels(&b.raster), "{filename}");
+            }
+        }
+    }
+}
@@ -0,0 +1,270 @@
+use pix::{rgb::Rgb, Raster};
+use png_pong::{
+    chunk::{Chromaticities, ColorProfile, Gamma, RenderingIntent, SRgb},
+    decode::Error as DecodeError,
+    Decoder, Encoder, PngRaster,
+};
+
+/// Get the 8-bit color samples of a decoded image.
+fn decode(file: &[u8], convert: bool) -> Vec<u8> {
+    let mut decoder = Decoder::new(file).expect("Not PNG");
+    if convert {
+        decoder = decoder.convert_to_srgb();
+    }
+    match decoder.into_steps().next().unwrap().unwrap().raster {
+        PngRaster::Gray8(raster) => raster.as_u8_slice().to_vec(),
+        PngRaster::Gray16(raster) => {
+            raster.as_u8_slice().iter().step_by(2).cloned().collect()
+        }
+        PngRaster::Rgb8(raster) => raster.as_u8_slice().to_vec(),
+        PngRaster::Palette(raster, palette, _) => raster
+            .as_u8_slice()
+            .iter()
+            .flat_map(|index| {
+                let color = palette.colors()[usize::from(*index)];
+                [
+                    Rgb::red(color).into(),
+                    Rgb::green(color).into(),
+                    Rgb::blue(color).into(),
+                ]
+            })
+            .collect(),
+        _ => panic!("Unexpected raster format"),
+    }
+}
+
+fn decode_file(filename: &str, convert: bool) -> Vec<u8> {
+    decode(&std::fs::read(filename).unwrap(), convert)
+}
+
+/// Get the fraction of samples that are within 2 of each other.
+fn matching(a: &[u8], b: &[u8]) -> f64 {
+    assert_eq!(a.len(), b.len());
+    let count = a
+        .iter()
+        .zip(b)
+        .filter(|(a, b)| a.abs_diff(**b) <= 2)
+        .count();
+    count as f64 / a.len() as f64
+}
+
+#[test]
+fn gamma() {
+    // The images only differ in file gamma (and the text showing it), so
+    // they should match once converted.
+    for color_type in ["0g16", "2c08", "3p04"] {
+        let file =
+            |gamma| format!("tests/pngsuite-gamma/g{gamma}n{color_type}.png");
+        let linear = decode_file(&file("10"), true);
+        for gamma in ["03", "04", "05", "07", "25"] {
+            let converted = decode_file(&file(gamma), true);
+            assert!(matching(&linear, &converted) > 0.9, "{gamma}{color_type}");
+        }
+        let unconverted = decode_file(&file("25"), false);
+        assert!(matching(&linear, &unconverted) < 0.75);
+    }
+}
+
+fn encode(
+    samples: &[u8],
+    gamma: f64,
+    chromaticities: Option<Chromaticities>,
+    srgb: bool,
+) -> Vec<u8> {
+    let raster = PngRaster::Rgb8(Raster::with_u8_buffer(
+        samples.len() as u32 / 3,
+        1,
+        samples,
+    ));
+    let mut file = Vec::new();
+    let mut encoder = Encoder::new(&mut file)
+        .into_step_enc()
+        .gamma(Gamma { gamma });
+    if let Some(chromaticities) = chromaticities {
+        encoder = encoder.chromaticities(chromaticities);
+    }
+    if srgb {
+        encoder = encoder.srgb(SRgb {
+            intent: RenderingIntent::Perceptual,
+        });
+    }
+    encoder.still(&raster).unwrap();
+    file
+}
+
+#[test]
+fn chromaticities() {
+    // Red and green primaries swapped
+    let swapped = Chromaticities {
+        white: (0.3127, 0.329),
+        red: (0.3, 0.6),
+        green: (0.64, 0.33),
+        blue: (0.15, 0.06),
+    };
+    let samples = [255, 0, 0, 0, 255, 0, 0, 0, 255, 255, 255, 255, 0, 0, 0];
+    let file = encode(&samples, 1.0 / 2.2, Some(swapped), false);
+    assert_eq!(
+        decode(&file, true),
+        [0, 255, 0, 255, 0, 0, 0, 0, 255, 255, 255, 255, 0, 0, 0]
+    );
+    assert_eq!(decode(&file, false), samples);
+}
+
+#[test]
+fn precedence() {
+    let samples = [64, 128, 192];
+    // Linear values are brightened
+    let file = encode(&samples, 1.0, None, false);
+    assert_eq!(decode(&file, true), [137, 188, 225]);
+    // sRGB chunk takes precedence over gAMA
+    let file = encode(&samples, 1.0, None, true);
+    assert_eq!(decode(&file, true), samples);
+    // gAMA of 1/2.2 is treated as sRGB
+    let file = encode(&samples, 1.0 / 2.2, None, false);
+    assert_eq!(decode(&file, true), samples);
+}
+
+/// Build an ICC profile from its color space and tags.
+fn profile(color_space: &[u8; 4], tags: &[(&[u8; 4], Vec<u8>)]) -> Vec<u8> {
+    let mut profile = vec![0; 128];
+    profile[16..20].copy_from_slice(color_space);
+    profile[20..24].copy_from_slice(b"XYZ ");
+    profile[36..40].copy_from_slice(b"acsp");
+    profile.extend((tags.len() as u32).to_be_bytes());
+    let mut offset = 132 + tags.len() * 12;
+    for (signature, data) in tags {
+        profile.extend(*signature);
+        profile.extend((offset as u32).to_be_bytes());
+        profile.extend((data.len() as u32).to_be_bytes());
+        offset += data.len();
+    }
+    for (_, data) in tags {
+        profile.extend(data);
+    }
+    let len = profile.len() as u32;
+    profile[..4].copy_from_slice(&len.to_be_bytes());
+    profile
+}
+
+/// XYZType tag
+fn xyz(xyz: [f64; 3]) -> Vec<u8> {
+    let mut tag = b"XYZ \0\0\0\0".to_vec();
+    for value in xyz {
+        tag.extend(((value * 65536.0).round() as i32).to_be_bytes());
+    }
+    tag
+}
+
+/// Linear parametricCurveType tag
+fn linear() -> Vec<u8> {
+    let mut tag = b"para\0\0\0\0\0\0\0\0".to_vec();
+    tag.extend(65536i32.to_be_bytes());
+    tag
+}
+
+/// Matrix/TRC profile with sRGB primaries, adapted to D50.
+fn matrix_trc(trc: Vec<u8>, swap: bool) -> Vec<u8> {
+    let (red, green) =
+        (xyz([0.4361, 0.2225, 0.0139]), xyz([0.3851, 0.7169, 0.0971]));
+    let (red, green) = if swap { (green, red) } else { (red, green) };
+    profile(
+        b"RGB ",
+        &[
+            (b"rXYZ", red),
+            (b"gXYZ", green),
+            (b"bXYZ", xyz([0.1431, 0.0606, 0.7141])),
+            (b"rTRC", trc.clone()),
+            (b"gTRC", trc.clone()),
+            (b"bTRC", trc),
+        ],
+    )
+}
+
+fn encode_profile(raster: &PngRaster, profile: Vec<u8>) -> Vec<u8> {
+    let mut file = Vec::new();
+    Encoder::new(&mut file)
+        .into_step_enc()
+        .color_profile(ColorProfile {
+            name: "Test".to_string(),
+            profile,
+        })
+        .still(raster)
+        .unwrap();
+    file
+}
+
+fn decode_error(file: &[u8]) -> DecodeError {
+    Decoder::new(file)
+        .unwrap()
+        .convert_to_srgb()
+        .into_steps()
+        .next()
+        .unwrap()
+        .unwrap_err()
+}
+
+#[test]
+fn icc_matrix_trc() {
+    let samples = [64, 128, 192, 255, 0, 0];
+    let raster = PngRaster::Rgb8(Raster::with_u8_buffer(2, 1, samples));
+
+    // Linear curves (parametric, empty curv, and a two entry table)
+    let table = b"curv\0\0\0\0\0\0\0\x02\0\0\xff\xff".to_vec();
+    let identity = b"curv\0\0\0\0\0\0\0\0".to_vec();
+    for trc in [linear(), identity, table] {
+        let file = encode_profile(&raster, matrix_trc(trc, false));
+        assert_eq!(decode(&file, true), [137, 188, 225, 255, 0, 0]);
+        assert_eq!(decode(&file, false), samples);
+    }
+
+    // Red and green colorants swapped
+    let file = encode_profile(&raster, matrix_trc(linear(), true));
+    assert_eq!(decode(&file, true), [188, 137, 225, 0, 255, 0]);
+
+    // Gamma of 2.2 (as u8Fixed8Number)
+    let gamma = b"curv\0\0\0\0\0\0\0\x01\x02\x33".to_vec();
+    let file = encode_profile(&raster, matrix_trc(gamma, false));
+    let converted = decode(&file, true);
+    for (converted, sample) in converted.iter().zip(samples) {
+        assert!(converted.abs_diff(sample) <= 2);
+    }
+}
+
+#[test]
+fn icc_gray() {
+    let raster = PngRaster::Gray8(Raster::with_u8_buffer(3, 1, [64, 128, 192]));
+    let gray = profile(b"GRAY", &[(b"kTRC", linear())]);
+    let file = encode_profile(&raster, gray);
+    assert_eq!(decode(&file, true), [137, 188, 225]);
+
+    // Profile doesn't match the color type
+    let file = encode_profile(&raster, matrix_trc(linear(), false));
+    assert!(matches!(decode_error(&file), DecodeError::ColorProfile));
+}
+
+#[test]
+fn icc_unsupported() {
+    let raster = PngRaster::Rgb8(Raster::with_u8_buffer(1, 1, [1, 2, 3]));
+    let lut = profile(b"RGB ", &[(b"A2B0", b"mft2\0\0\0\0".to_vec())]);
+    let file = encode_profile(&raster, lut);
+    assert!(matches!(decode_error(&file), DecodeError::ProfileLut));
+    // Only an error when converting
+    assert_eq!(decode(&file, false), [1, 2, 3]);
+
+    let mut invalid = matrix_trc(linear(), false);
+    invalid[36..40].copy_from_slice(b"xxxx");
+    let file = encode_profile(&raster, invalid);
+    assert!(matches!(decode_error(&file), DecodeError::ColorProfile));
+
+    let truncated = matrix_trc(linear(), false)[..200].to_vec();
+    let file = encode_profile(&raster, truncated);
+    assert!(matches!(decode_error(&file), DecodeError::ColorProfile));
+}
+
+#[test]
+fn icc_srgb() {
+    // GIMP's built-in sRGB profile (parametric curves) changes nothing
+    let unconverted = decode_file("tests/png/0.png", false);
+    let converted = decode_file("tests/png/0.png", true);
+    assert_eq!(matching(&unconverted, &converted), 1.0);
+}
@@ -0,0 +1,242 @@
+use pix::Raster;
+use png_pong::{
+    chunk::{Chunk, ChunkPosition, Custom, CustomChunk, Unknown},
+    decode::Error as DecodeError,
+    encode::Error as EncodeError,
+    Decoder, Encoder, PngRaster,
+};
+
+/// Preview region of an image (before the image data)
+#[derive(Clone, Debug, PartialEq)]
+struct PreviewRegion {
+    x: u16,
+    y: u16,
+}
+
+impl CustomChunk for PreviewRegion {
+    const NAME: [u8; 4] = *b"prVw";
+    const POSITION: ChunkPosition = ChunkPosition::BeforeImageData;
+
+    fn parse(data: &[u8]) -> Result<Self, DecodeError> {
+        let [x0, x1, y0, y1] = data else {
+            return Err(DecodeError::ChunkLength(Self::NAME));
+        };
+        Ok(PreviewRegion {
+            x: u16::from_be_bytes([*x0, *x1]),
+            y: u16::from_be_bytes([*y0, *y1]),
+        })
+    }
+
+    fn write(&self) -> Result<Vec<u8>, EncodeError> {
+        Ok([self.x.to_be_bytes(), self.y.to_be_bytes()].concat())
+    }
+}
+
+/// Sprite bounds (anywhere)
+#[derive(Clone, Debug, PartialEq)]
+struct SpriteBounds(Vec<u8>);
+
+impl CustomChunk for SpriteBounds {
+    const NAME: [u8; 4] = *b"sbDt";
+    const POSITION: ChunkPosition = ChunkPosition::AfterImageData;
+
+    fn parse(data: &[u8]) -> Result<Self, DecodeError> {
+        Ok(SpriteBounds(data.to_vec()))
+    }
+
+    fn write(&self) -> Result<Vec<u8>, EncodeError> {
+        Ok(self.0.clone())
+    }
+}
+
+/// Get the chunks of a 1x1 image.
+fn image_chunks() -> Vec<Chunk> {
+    let mut file = Vec::new();
+    Encoder::new(&mut file)
+        .into_step_enc()
+        .still(&PngRaster::Gray8(Raster::with_u8_buffer(1, 1, [0])))
+        .unwrap();
+    Decoder::new(file.as_slice())
+        .unwrap()
+        .into_chunks()
+        .map(Result::unwrap)
+        .collect()
+}
+
+/// Encode chunks with the custom chunk types registered.
+fn encode(chunks: Vec<Chunk>) -> Result<Vec<u8>, EncodeError> {
+    let mut file = Vec::new();
+    let mut encoder = Encoder::new(&mut file)
+        .custom_chunk::<PreviewRegion>()
+        .custom_chunk::<SpriteBounds>()
+        .into_chunk_enc();
+    for mut chunk in chunks {
+        encoder.encode(&mut chunk)?;
+    }
+    Ok(file)
+}
+
+fn decoder(file: &[u8]) -> Decoder<&[u8]> {
+    Decoder::new(file)
+        .unwrap()
+        .custom_chunk::<PreviewRegion>()
+        .custom_chunk::<SpriteBounds>()
+}
+
+#[test]
+fn roundtrip() {
+    let mut chunks = image_chunks();
+    let end = chunks.len() - 1;
+    chunks.insert(end, Chunk::Custom(Custom::new(SpriteBounds(vec![1, 2]))));
+    chunks.insert(1, Chunk::Custom(Custom::new(PreviewRegion { x: 3, y: 4 })));
+    let file = encode(chunks).unwrap();
+
+    let customs: Vec<Custom> = decoder(&file)
+        .into_chunks()
+        .filter_map(|chunk| match chunk.unwrap() {
+            Chunk::Custom(custom) => Some(custom),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(customs.len(), 2);
+    assert_eq!(customs[0].name(), *b"prVw");
+    assert_eq!(
+        customs[0].get::<PreviewRegion>(),
+        Some(&PreviewRegion { x: 3, y: 4 })
+    );
+    assert_eq!(customs[0].get::<SpriteBounds>(), None);
+    let mut customs = customs.into_iter();
+    let preview = customs.next().unwrap();
+    let preview = preview.into_inner::<SpriteBounds>().unwrap_err();
+    assert!(preview.into_inner::<PreviewRegion>().is_ok());
+    assert_eq!(
+        customs
+            .next()
+            .unwrap()
+            .into_inner::<SpriteBounds>()
+            .unwrap(),
+        SpriteBounds(vec![1, 2])
+    );
+
+    // Kept in the metadata by steps
+    let mut steps = decoder(&file).into_steps();
+    let raster = steps.next().unwrap().unwrap().raster;
+    assert!(steps.next().is_none());
+    let metadata = steps.metadata().clone();
+    let positions: Vec<_> = metadata
+        .custom_chunks
+        .iter()
+        .map(|(position, custom)| (*position, custom.name()))
+        .collect();
+    assert_eq!(
+        positions,
+        [
+            (ChunkPosition::BeforePalette, *b"prVw"),
+            (ChunkPosition::AfterImageData, *b"sbDt"),
+        ]
+    );
+
+    // Written back by the step encoder
+    let mut out = Vec::new();
+    Encoder::new(&mut out)
+        .custom_chunk::<PreviewRegion>()
+        .custom_chunk::<SpriteBounds>()
+        .into_step_enc()
+        .metadata(metadata.clone())
+        .still(&raster)
+        .unwrap();
+    let mut steps = decoder(&out).into_steps();
+    assert!(steps.next().unwrap().is_ok());
+    assert!(steps.next().is_none());
+    let customs = &steps.metadata().custom_chunks;
+    assert_eq!(customs.len(), 2);
+    assert_eq!(customs[0].0, ChunkPosition::BeforePalette);
+    assert_eq!(
+        customs[0].1.get::<PreviewRegion>(),
+        Some(&PreviewRegion { x: 3, y: 4 })
+    );
+    assert_eq!(customs[1].0, ChunkPosition::AfterImageData);
+    assert_eq!(
+        customs[1].1.get::<SpriteBounds>(),
+        Some(&SpriteBounds(vec![1, 2]))
+    );
+
+    // Not registered on the step encoder
+    let result = Encoder::new(&mut Vec::new())
+        .into_step_enc()
+        .metadata(metadata)
+        .still(&raster);
+    assert!(matches!(
+        result,
+        Err(EncodeError::UnregisteredChunk(name)) if &name == b"prVw"
+    ));
+
+    // Unknown if not registered
+    let unknown = Decoder::new(file.as_slice())
+        .unwrap()
+        .into_chunks()
+        .filter(|chunk| matches!(chunk, Ok(Chunk::Unknown(_))))
+        .count();
+    assert_eq!(unknown, 2);
+}
+
+#[test]
+fn position() {
+    // Encoding after the image data
+    let mut chunks = image_chunks();
+    let end = chunks.len() - 1;
+    chunks.insert(
+        end,
+        Chunk::Custom(Custom::new(PreviewRegion { x: 0, y: 0 })),
+    );
+    assert!(matches!(
+        encode(chunks),
+        Err(EncodeError::ChunkOrder(name)) if &name == b"prVw"
+    ));
+
+    // Decoding after the image data
+    let mut chunks = image_chunks();
+    let end = chunks.len() - 1;
+    chunks.insert(
+        end,
+        Chunk::Unknown(Unknown {
+            name: *b"prVw",
+            data: vec![0; 4],
+        }),
+    );
+    let file = encode(chunks).unwrap();
+    let mut steps = decoder(&file).into_steps();
+    assert!(steps.next().unwrap().is_ok());
+    assert!(matches!(steps.next(), Some(Err(DecodeError::ChunkOrder))));
+    assert!(decoder(&file).into_chunks().all(|chunk| chunk.is_ok()));
+}
+
+#[test]
+fn unregistered() {
+    let mut chunks = image_chunks();
+    chunks.insert(1, Chunk::Custom(Custom::new(PreviewRegion { x: 0, y: 0 })));
+    let mut file = Vec::new();
+    let mut encoder = Encoder::new(&mut file).into_chunk_enc();
+    let error = chunks
+        .into_iter()
+        .try_for_each(|mut chunk| encoder.encode(&mut chunk));
+    assert!(matches!(
+        error,
+        Err(EncodeError::UnregisteredChunk(name)) if &name == b"prVw"
+    ));
+
+    // Parse errors
+    let mut chunks = image_chunks();
+    chunks.insert(
+        1,
+        Chunk::Unknown(Unknown {
+            name: *b"prVw",
+            data: vec![0; 3],
+        }),
+    );
+    let file = encode(chunks).unwrap();
+    assert!(decoder(&file).into_chunks().any(|chunk| matches!(
+        chunk,
+        Err(DecodeError::ChunkLength(name)) if &name == b"prVw"
+    )));
+}
@@ -0,0 +1,351 @@
+use pix::{
+    rgb::{Rgb, SRgb8},
+    Raster,
+};
+use png_pong::{
+    chunk::{Chunk, SignificantBits},
+    decode::Error as DecodeError,
+    encode::Error as EncodeError,
+    Decoder, Encoder, PngRaster, Step,
+};
+
+const WIDTH: u32 = 13;
+const HEIGHT: u32 = 9;
+
+fn decode(filename: &str) -> PngRaster {
+    decode_file(&std::fs::read(filename).unwrap())
+}
+
+fn decode_file(file: &[u8]) -> PngRaster {
+    Decoder::new(file)
+        .expect("Not PNG")
+        .into_steps()
+        .next()
+        .unwrap()
+        .unwrap()
+        .raster
+}
+
+// Sample values of the generated test images
+fn samples(bit_depth: u8) -> Vec<u8> {
+    let max = 1 << bit_depth;
+    (0..HEIGHT)
+        .flat_map(|y| (0..WIDTH).map(move |x| ((x + 2 * y) % max) as u8))
+        .collect()
+}
+
+#[test]
+fn gray() {
+    for (bit_depth, scale) in [(1, 255), (2, 85), (4, 17)] {
+        for interlace in ["n", "i"] {
+            let filename =
+                format!("tests/png/depth/g{bit_depth}{interlace}.png");
+            let PngRaster::Gray8(raster) = decode(&filename) else {
+                panic!("{filename} should be Gray8");
+            };
+            let expected: Vec<u8> =
+                samples(bit_depth).iter().map(|v| v * scale).collect();
+            assert_eq!((raster.width(), raster.height()), (WIDTH, HEIGHT));
+            assert_eq!(raster.as_u8_slice(), expected, "{filename}");
+        }
+    }
+}
+
+#[test]
+fn palette() {
+    for bit_depth in [1, 2, 4] {
+        for interlace in ["n", "i"] {
+            let filename =
+                format!("tests/png/depth/p{bit_depth}{interlace}.png");
+            let PngRaster::Palette(raster, palette, _) = decode(&filename)
+            else {
+                panic!("{filename} should be Palette");
+            };
+            assert_eq!(palette.len(), 1 << bit_depth);
+            assert_eq!(palette.entry(3 % palette.len()).unwrap(), {
+                let j = (3 % palette.len()) as u8;
+                SRgb8::new(j * 16, 255 - j * 16, j)
+            });
+            assert_eq!(raster.as_u8_slice(), samples(bit_depth), "{filename}");
+
+            // Indices are expanded to colors
+            let rgb: Raster<SRgb8> =
+                PngRaster::Palette(raster, palette, vec![]).into();
+            assert_eq!(rgb.pixel(1, 0), SRgb8::new(16, 239, 1));
+        }
+    }
+}
+
+// Encode a raster (automatic bit depth if `None`), returning the bit depth of
+// the file and the decoded raster
+fn roundtrip(
+    raster: &PngRaster,
+    bit_depth: Option<u8>,
+    interlace: bool,
+) -> Result<(u8, PngRaster), EncodeError> {
+    let mut file = Vec::new();
+    let mut encoder = Encoder::new(&mut file);
+    encoder = match bit_depth {
+        Some(bit_depth) => encoder.bit_depth(bit_depth),
+        None => encoder.auto_bit_depth(),
+    };
+    if interlace {
+        encoder = encoder.interlace();
+    }
+    encoder.into_step_enc().still(raster)?;
+
+    let Some(Ok(Chunk::ImageHeader(header))) =
+        Decoder::new(file.as_slice()).unwrap().into_chunks().next()
+    else {
+        panic!("No image header");
+    };
+    Ok((header.bit_depth, decode_file(&file)))
+}
+
+#[test]
+fn encode_gray() {
+    for (bit_depth, scale) in [(1, 255), (2, 85), (4, 17)] {
+        let samples: Vec<u8> =
+            samples(bit_depth).iter().map(|v| v * scale).collect();
+        let raster = PngRaster::Gray8(Raster::with_u8_buffer(
+            WIDTH,
+            HEIGHT,
+            samples.as_slice(),
+        ));
+        for interlace in [false, true] {
+            for (request, expected) in [(None, bit_depth), (Some(4), 4)] {
+                let (depth, decoded) =
+                    roundtrip(&raster, request, interlace).unwrap();
+                assert_eq!(depth, expected);
+                let PngRaster::Gray8(decoded) = decoded else {
+                    panic!("Should be Gray8");
+                };
+                assert_eq!(decoded.as_u8_slice(), samples);
+            }
+        }
+    }
+
+    // 128 doesn't fit in 4 bits
+    let raster = PngRaster::Gray8(Raster::with_u8_buffer(1, 1, [128]));
+    let result = roundtrip(&raster, Some(4), false);
+    assert!(matches!(result, Err(EncodeError::BitDepth(4))));
+    assert_eq!(roundtrip(&raster, None, false).unwrap().0, 8);
+}
+
+#[test]
+fn encode_palette() {
+    for bit_depth in [1, 2, 4] {
+        let raster = decode(&format!("tests/png/depth/p{bit_depth}n.png"));
+        for interlace in [false, true] {
+            let (depth, decoded) = roundtrip(&raster, None, interlace).unwrap();
+            assert_eq!(depth, bit_depth);
+            let PngRaster::Palette(decoded, palette, _) = decoded else {
+                panic!("Should be Palette");
+            };
+            assert_eq!(palette.len(), 1 << bit_depth);
+            assert_eq!(decoded.as_u8_slice(), samples(bit_depth));
+        }
+        assert_eq!(roundtrip(&raster, Some(8), false).unwrap().0, 8);
+    }
+
+    // 4 colors don't fit in 1 bit
+    let raster = decode("tests/png/depth/p2n.png");
+    let result = roundtrip(&raster, Some(1), false);
+    assert!(matches!(result, Err(EncodeError::BitDepth(1))));
+}
+
+#[test]
+fn encode_animation() {
+    // The second frame needs more bits than the first
+    let frames = [[0, 255], [0, 100]];
+    let encode = |encoder: Encoder<&mut Vec<u8>>| {
+        let mut encoder = encoder.into_step_enc().animate(2, 0);
+        for frame in frames {
+            encoder.encode(&Step {
+                raster: PngRaster::Gray8(Raster::with_u8_buffer(2, 1, frame)),
+                delay: 100,
+                frame: None,
+            })?;
+        }
+        encoder.finish()
+    };
+
+    let mut file = Vec::new();
+    encode(Encoder::new(&mut file).auto_bit_depth()).unwrap();
+    let Some(Ok(Chunk::ImageHeader(header))) =
+        Decoder::new(file.as_slice()).unwrap().into_chunks().next()
+    else {
+        panic!("No image header");
+    };
+    assert_eq!(header.bit_depth, 8);
+    let steps = Decoder::new(file.as_slice())
+        .unwrap()
+        .into_frames()
+        .map(|step| match step.unwrap().raster {
+            PngRaster::Gray8(raster) => raster.as_u8_slice().to_vec(),
+            _ => panic!("Should be Gray8"),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(steps, frames);
+
+    // Explicit bit depths still apply to every frame
+    let mut file = Vec::new();
+    let result = encode(Encoder::new(&mut file).bit_depth(1));
+    assert!(matches!(result, Err(EncodeError::BitDepth(1))));
+}
+
+#[test]
+fn missing_palette() {
+    let raster = decode("tests/png/depth/p2n.png");
+    for bit_depth in [2, 8] {
+        let mut file = Vec::new();
+        Encoder::new(&mut file)
+            .bit_depth(bit_depth)
+            .into_step_enc()
+            .still(&raster)
+            .unwrap();
+        let chunks = Decoder::new(file.as_slice())
+            .unwrap()
+            .into_chunks()
+            .map(Result::unwrap)
+            .filter(|chunk| !matches!(chunk, Chunk::Palette(_)));
+        let mut file = Vec::new();
+        let mut encoder = Encoder::new(&mut file).into_chunk_enc();
+        for mut chunk in chunks {
+            encoder.encode(&mut chunk).unwrap();
+        }
+        let mut steps = Decoder::new(file.as_slice()).unwrap().into_steps();
+        assert!(
+            matches!(steps.next(), Some(Err(DecodeError::NoPalette))),
+            "{bit_depth}"
+        );
+    }
+}
+
+/// Decode samples (palette colors for palette images), optionally shifted to
+/// their significant bits.
+fn significant(file: &[u8], shift: bool) -> (PngRaster, Vec<u16>) {
+    let mut decoder = Decoder::new(file).expect("Not PNG");
+    if shift {
+        decoder = decoder.shift_significant_bits();
+    }
+    let mut steps = decoder.into_steps();
+    let raster = steps.next().unwrap().unwrap().raster;
+    let samples = match &raster {
+        PngRaster::Rgb8(r) => {
+            r.as_u8_slice().iter().map(|v| (*v).into()).collect()
+        }
+        PngRaster::Rgb16(r) => r
+            .as_u8_slice()
+            .chunks_exact(2)
+            .map(|v| u16::from_be_bytes([v[0], v[1]]))
+            .collect(),
+        PngRaster::Palette(_, palette, _) => palette
+            .colors()
+            .iter()
+            .flat_map(|c| {
+                [
+                    Rgb::red(*c).into(),
+                    Rgb::green(*c).into(),
+                    Rgb::blue(*c).into(),
+                ]
+            })
+            .map(|v: u8| v.into())
+            .collect(),
+        _ => panic!("Unexpected raster format"),
+    };
+    (raster, samples)
+}
+
+#[test]
+fn significant_bits() {
+    for (name, bits, depth) in [
+        ("cs3n2c16", 13, 16),
+        ("cs3n3p08", 3, 8),
+        ("cs5n2c08", 5, 8),
+        ("cs5n3p08", 5, 8),
+        ("cs8n2c08", 8, 8),
+    ] {
+        let file =
+            std::fs::read(format!("tests/pngsuite-ancillary/{name}.png"))
+                .unwrap();
+        let metadata = {
+            let mut steps = Decoder::new(file.as_slice()).unwrap().into_steps();
+            steps.next().unwrap().unwrap();
+            steps.metadata().clone()
+        };
+        let expected =
+            (bits != 8).then_some(SignificantBits::Rgb(bits, bits, bits));
+        assert_eq!(metadata.significant_bits, expected, "{name}");
+
+        let (_, full) = significant(&file, false);
+        let (shifted, samples) = significant(&file, true);
+        let expected: Vec<u16> =
+            full.iter().map(|v| v >> (depth - bits)).collect();
+        assert_eq!(samples, expected, "{name}");
+
+        // Scaled back up exactly
+        let mut out = Vec::new();
+        Encoder::new(&mut out)
+            .into_step_enc()
+            .metadata(metadata)
+            .scale_significant_bits()
+            .still(&shifted)
+            .unwrap();
+        assert_eq!(significant(&out, false).1, full, "{name}");
+        assert_eq!(significant(&out, true).1, samples, "{name}");
+    }
+}
+
+#[test]
+fn encode_significant_bits() {
+    let raster = PngRaster::Gray8(Raster::with_u8_buffer(4, 1, [0, 1, 6, 7]));
+    let encode = |raster: &PngRaster, bits: SignificantBits| {
+        let mut out = Vec::new();
+        Encoder::new(&mut out)
+            .auto_bit_depth()
+            .into_step_enc()
+            .significant_bits(bits)
+            .scale_significant_bits()
+            .still(raster)?;
+        Ok::<_, EncodeError>(out)
+    };
+
+    // 3-bit values scaled up to 8 bits
+    let file = encode(&raster, SignificantBits::Gray(3)).unwrap();
+    let Some(Ok(Chunk::ImageHeader(header))) =
+        Decoder::new(file.as_slice()).unwrap().into_chunks().next()
+    else {
+        panic!("No image header");
+    };
+    assert_eq!(header.bit_depth, 8);
+    let PngRaster::Gray8(decoded) = decode_file(&file) else {
+        panic!("Should be Gray8");
+    };
+    assert_eq!(decoded.as_u8_slice(), [0, 36, 219, 255]);
+
+    // Lowered bit depths limit the significant bits
+    let full = PngRaster::Gray8(Raster::with_u8_buffer(2, 1, [0, 31]));
+    let file = encode(&full, SignificantBits::Gray(5)).unwrap();
+    let mut steps = Decoder::new(file.as_slice()).unwrap().into_steps();
+    let PngRaster::Gray8(decoded) = steps.next().unwrap().unwrap().raster
+    else {
+        panic!("Should be Gray8");
+    };
+    assert_eq!(decoded.as_u8_slice(), [0, 255]);
+    assert_eq!(
+        steps.metadata().significant_bits,
+        Some(SignificantBits::Gray(1))
+    );
+
+    // Values must fit in the significant bits
+    assert!(matches!(
+        encode(&raster, SignificantBits::Gray(2)),
+        Err(EncodeError::BitDepth(2))
+    ));
+    // And the significant bits must match the color type
+    assert!(matches!(
+        encode(&raster, SignificantBits::Rgb(3, 3, 3)),
+        Err(EncodeError::ChunkValue(name)) if &name == b"sBIT"
+    ));
+}
@@ -0,0 +1,265 @@
+use pix::Raster;
+use png_pong::{
+    chunk::{BlendOp, DisposeOp, Exif, FrameControl, Orientation},
+    decode::Error as DecodeError,
+    encode::Error as EncodeError,
+    Decoder, Encoder, PngRaster, Step,
+};
+
+/// Build EXIF data with IFD0 (Make, Model, Orientation and the Exif IFD
+/// pointer) and an Exif IFD (DateTimeOriginal).
+fn exif(big_endian: bool, orientation: u16) -> Vec<u8> {
+    let u16 = |value: u16| {
+        if big_endian {
+            value.to_be_bytes()
+        } else {
+            value.to_le_bytes()
+        }
+    };
+    let u32 = |value: u32| {
+        if big_endian {
+            value.to_be_bytes()
+        } else {
+            value.to_le_bytes()
+        }
+    };
+    let mut data = Vec::new();
+    data.extend(if big_endian { b"MM\0*" } else { b"II*\0" });
+    data.extend(u32(8));
+    // IFD0 at 8, with 4 entries (ending at 8 + 2 + 4 * 12 + 4 = 62)
+    data.extend(u16(4));
+    // Make (fits in the entry)
+    data.extend(u16(0x010F));
+    data.extend(u16(2));
+    data.extend(u32(4));
+    data.extend(b"Foo\0");
+    // Model at 62
+    data.extend(u16(0x0110));
+    data.extend(u16(2));
+    data.extend(u32(8));
+    data.extend(u32(62));
+    // Orientation
+    data.extend(u16(0x0112));
+    data.extend(u16(3));
+    data.extend(u32(1));
+    data.extend(u16(orientation));
+    data.extend([0, 0]);
+    // Exif IFD at 70
+    data.extend(u16(0x8769));
+    data.extend(u16(4));
+    data.extend(u32(1));
+    data.extend(u32(70));
+    // No next IFD
+    data.extend(u32(0));
+    data.extend(b"Bar 100\0");
+    // Exif IFD with 1 entry, DateTimeOriginal at 88
+    data.extend(u16(1));
+    data.extend(u16(0x9003));
+    data.extend(u16(2));
+    data.extend(u32(20));
+    data.extend(u32(88));
+    data.extend(u32(0));
+    data.extend(b"2020:02:29 12:30:00\0");
+    data
+}
+
+fn encode(raster: &PngRaster, exif: Exif) -> Result<Vec<u8>, EncodeError> {
+    let mut file = Vec::new();
+    Encoder::new(&mut file)
+        .into_step_enc()
+        .exif(exif)
+        .still(raster)?;
+    Ok(file)
+}
+
+fn decode(file: &[u8], orient: bool) -> Result<PngRaster, DecodeError> {
+    let mut decoder = Decoder::new(file).unwrap();
+    if orient {
+        decoder = decoder.apply_orientation();
+    }
+    Ok(decoder.into_steps().next().unwrap()?.raster)
+}
+
+#[test]
+fn tags() {
+    for big_endian in [false, true] {
+        let exif = Exif {
+            data: exif(big_endian, 6),
+        };
+        assert_eq!(exif.make().as_deref(), Some("Foo"));
+        assert_eq!(exif.model().as_deref(), Some("Bar 100"));
+        assert_eq!(exif.orientation(), Some(Orientation::Rotate90));
+        assert_eq!(
+            exif.date_time_original().as_deref(),
+            Some("2020:02:29 12:30:00")
+        );
+    }
+
+    // Missing and invalid tags
+    let exif = Exif {
+        data: exif(false, 9),
+    };
+    assert_eq!(exif.orientation(), None);
+    let exif = Exif {
+        data: b"MM\0*\0\0\0\x08\0\0".to_vec(),
+    };
+    assert_eq!(exif.make(), None);
+    assert_eq!(exif.date_time_original(), None);
+}
+
+#[test]
+fn roundtrip() {
+    let raster = PngRaster::Gray8(Raster::with_u8_buffer(1, 1, [0]));
+    let exif = Exif {
+        data: exif(true, 1),
+    };
+    let file = encode(&raster, exif.clone()).unwrap();
+    let mut steps = Decoder::new(file.as_slice()).unwrap().into_steps();
+    steps.next().unwrap().unwrap();
+    assert_eq!(steps.metadata().exif, Some(exif));
+
+    // Invalid TIFF header
+    let exif = Exif {
+        data: b"TIFF".to_vec(),
+    };
+    assert!(matches!(
+        encode(&raster, exif),
+        Err(EncodeError::ChunkValue(name)) if &name == b"eXIf"
+    ));
+}
+
+#[test]
+fn orientation() {
+    // 3x2 image:
+    // 0 1 2
+    // 3 4 5
+    let raster =
+        PngRaster::Gray8(Raster::with_u8_buffer(3, 2, [0, 1, 2, 3, 4, 5]));
+    for (orientation, width, pixels) in [
+        (1, 3, [0, 1, 2, 3, 4, 5]),
+        (2, 3, [2, 1, 0, 5, 4, 3]),
+        (3, 3, [5, 4, 3, 2, 1, 0]),
+        (4, 3, [3, 4, 5, 0, 1, 2]),
+        (5, 2, [0, 3, 1, 4, 2, 5]),
+        (6, 2, [3, 0, 4, 1, 5, 2]),
+        (7, 2, [5, 2, 4, 1, 3, 0]),
+        (8, 2, [2, 5, 1, 4, 0, 3]),
+    ] {
+        let exif = Exif {
+            data: exif(false, orientation),
+        };
+        let file = encode(&raster, exif).unwrap();
+        let PngRaster::Gray8(oriented) = decode(&file, true).unwrap() else {
+            panic!("Should be Gray8");
+        };
+        assert_eq!(oriented.width(), width, "{orientation}");
+        assert_eq!(oriented.as_u8_slice(), pixels, "{orientation}");
+
+        // Not applied by default
+        let PngRaster::Gray8(stored) = decode(&file, false).unwrap() else {
+            panic!("Should be Gray8");
+        };
+        assert_eq!(stored.as_u8_slice(), [0, 1, 2, 3, 4, 5]);
+    }
+}
+
+#[test]
+fn orientation_frame() {
+    // 3x2 animation, where the second frame region covers 1 and 2:
+    // 0 1 2
+    // 3 4 5
+    let raster = PngRaster::Rgba8(Raster::with_u8_buffer(
+        3,
+        2,
+        (0..6).flat_map(|i| [i, 0, 0, 255]).collect::<Vec<u8>>(),
+    ));
+    let frame = FrameControl {
+        sequence: 0,
+        width: 2,
+        height: 1,
+        x_offset: 1,
+        y_offset: 0,
+        delay_num: 1,
+        delay_den: 10,
+        dispose_op: DisposeOp::None,
+        blend_op: BlendOp::Source,
+    };
+    for orientation in 1..=8 {
+        let mut file = Vec::new();
+        let mut encoder = Encoder::new(&mut file)
+            .into_step_enc()
+            .exif(Exif {
+                data: exif(false, orientation),
+            })
+            .animate(2, 0);
+        for frame in [None, Some(frame)] {
+            encoder
+                .encode(&Step {
+                    raster: raster.clone(),
+                    delay: 100,
+                    frame,
+                })
+                .unwrap();
+        }
+        encoder.finish().unwrap();
+
+        let steps = Decoder::new(file.as_slice())
+            .unwrap()
+            .apply_orientation()
+            .into_steps()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let PngRaster::Rgba8(oriented) = &steps[1].raster else {
+            panic!("Should be Rgba8");
+        };
+        let region = steps[1].frame.unwrap();
+        assert!(region.x_offset + region.width <= oriented.width());
+        assert!(region.y_offset + region.height <= oriented.height());
+        let mut pixels = Vec::new();
+        for y in region.y_offset..region.y_offset + region.height {
+            for x in region.x_offset..region.x_offset + region.width {
+                let i = (y * oriented.width() + x) as usize * 4;
+                pixels.push(oriented.as_u8_slice()[i]);
+            }
+        }
+        pixels.sort();
+        assert_eq!(pixels, [1, 2], "{orientation}");
+    }
+}
+
+#[test]
+fn invalid() {
+    let raster = PngRaster::Gray8(Raster::with_u8_buffer(1, 1, [0]));
+    let mut file = encode(
+        &raster,
+        Exif {
+            data: exif(false, 1),
+        },
+    )
+    .unwrap();
+    // Corrupt the byte order (and fix the CRC)
+    let start = file.windows(4).position(|w| w == b"eXIf").unwrap();
+    let len = u32::from_be_bytes(file[start - 4..start].try_into().unwrap());
+    let end = start + 4 + len as usize;
+    file[start + 4..start + 6].copy_from_slice(b"XX");
+    let crc = crc32(&file[start..end]);
+    file[end..end + 4].copy_from_slice(&crc.to_be_bytes());
+    assert!(matches!(
+        decode(&file, false),
+        Err(DecodeError::ChunkValue(name)) if &name == b"eXIf"
+    ));
+
+    let file = std::fs::read("tests/png/bad/huge_eXIf_chunk.png").unwrap();
+    assert!(decode(&file, false).is_err());
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = u32::MAX;
+    for byte in data {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
@@ -0,0 +1,260 @@
+use pix::Raster;
+use png_pong::{
+    chunk::{
+        Chunk, Equation, GifApplicationExt, GifGraphicControlExt, ImageOffset,
+        OffsetUnit, PixelCalibration, ScaleUnit, Stereo, SubjectPhysical,
+        Unknown,
+    },
+    decode::Error as DecodeError,
+    encode::Error as EncodeError,
+    Decoder, Encoder, PngMetadata, PngRaster,
+};
+
+fn raster() -> PngRaster {
+    PngRaster::Gray8(Raster::with_u8_buffer(1, 1, [0]))
+}
+
+fn encode(metadata: PngMetadata) -> Result<Vec<u8>, EncodeError> {
+    let mut file = Vec::new();
+    Encoder::new(&mut file)
+        .into_step_enc()
+        .metadata(metadata)
+        .still(&raster())?;
+    Ok(file)
+}
+
+fn decode(file: &[u8]) -> Result<PngMetadata, DecodeError> {
+    let mut steps = Decoder::new(file).unwrap().into_steps();
+    for step in steps.by_ref() {
+        step?;
+    }
+    Ok(steps.metadata().clone())
+}
+
+/// Encode an image with a raw chunk before IDAT, and decode it.
+fn decode_chunk(name: &[u8; 4], data: &[u8]) -> Result<Chunk, DecodeError> {
+    let mut file = Vec::new();
+    let mut encoder = Encoder::new(&mut file).into_chunk_enc();
+    let chunks =
+        Decoder::new(encode(PngMetadata::default()).unwrap().as_slice())
+            .unwrap()
+            .into_chunks()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>();
+    for (i, mut chunk) in chunks.into_iter().enumerate() {
+        if i == 1 {
+            encoder
+                .encode(&mut Chunk::Unknown(Unknown {
+                    name: *name,
+                    data: data.to_vec(),
+                }))
+                .unwrap();
+        }
+        encoder.encode(&mut chunk).unwrap();
+    }
+    Decoder::new(file.as_slice())
+        .unwrap()
+        .into_chunks()
+        .nth(1)
+        .unwrap()
+}
+
+fn calibration(equation: Equation, params: &[&str]) -> PixelCalibration {
+    PixelCalibration {
+        name: "temperature".to_string(),
+        x0: 0,
+        x1: 1000,
+        equation,
+        unit: "K".to_string(),
+        params: params.iter().map(|param| param.to_string()).collect(),
+    }
+}
+
+#[test]
+fn roundtrip() {
+    let metadata = PngMetadata {
+        offset: Some(ImageOffset {
+            x: -20,
+            y: 300,
+            unit: OffsetUnit::Micrometer,
+        }),
+        calibration: Some(calibration(Equation::Linear, &["273.15", "1e2"])),
+        subject_physical: Some(SubjectPhysical {
+            unit: ScaleUnit::Meter,
+            width: 0.5,
+            height: 1.25e-6,
+        }),
+        stereo: Some(Stereo::DivergingFuse),
+        ..Default::default()
+    };
+    let decoded = decode(&encode(metadata.clone()).unwrap()).unwrap();
+    assert_eq!(decoded.offset, metadata.offset);
+    assert_eq!(decoded.calibration, metadata.calibration);
+    assert_eq!(decoded.subject_physical, metadata.subject_physical);
+    assert_eq!(decoded.stereo, metadata.stereo);
+}
+
+#[test]
+fn physical_value() {
+    let linear = calibration(Equation::Linear, &["273.15", "100"]);
+    assert_eq!(linear.physical_value(0, 255), Some(273.15));
+    assert_eq!(linear.physical_value(255, 255), Some(373.15));
+    let exponential = calibration(Equation::Exponential, &["0", "2", "1"]);
+    let value = exponential.physical_value(255, 255).unwrap();
+    assert!((value - 2.0 * 1f64.exp()).abs() < 1e-9);
+    let base =
+        calibration(Equation::ArbitraryExponential, &["1", "1", "2", "10"]);
+    assert_eq!(base.physical_value(255, 255), Some(101.0));
+    let hyperbolic = calibration(Equation::Hyperbolic, &["0", "1", "-1", "0"]);
+    let value = hyperbolic.physical_value(255, 255).unwrap();
+    assert!((value - (-1f64).sinh()).abs() < 1e-9);
+
+    // Invalid parameters
+    assert_eq!(
+        calibration(Equation::Linear, &["1"]).physical_value(0, 1),
+        None
+    );
+    assert_eq!(
+        calibration(Equation::Linear, &["1", "inf"]).physical_value(0, 1),
+        None
+    );
+}
+
+#[test]
+fn ascii_floats() {
+    let Ok(Chunk::SubjectPhysical(scale)) =
+        decode_chunk(b"sCAL", b"\x021.5E+2\0.25")
+    else {
+        panic!("Not sCAL");
+    };
+    assert_eq!(scale.unit, ScaleUnit::Radian);
+    assert_eq!((scale.width, scale.height), (150.0, 0.25));
+
+    let mut data = b"depth\0".to_vec();
+    data.extend([0, 0, 0, 0, 0, 0, 0xFF, 0xFF, 1, 3]);
+    data.extend(b"m\0-1.\0+2\0.5e-1");
+    let Ok(Chunk::PixelCalibration(calibration)) = decode_chunk(b"pCAL", &data)
+    else {
+        panic!("Not pCAL");
+    };
+    assert_eq!(calibration.name, "depth");
+    assert_eq!((calibration.x0, calibration.x1), (0, 65535));
+    assert_eq!(calibration.equation, Equation::Exponential);
+    assert_eq!(calibration.unit, "m");
+    assert_eq!(calibration.params, ["-1.", "+2", ".5e-1"]);
+
+    // Not ASCII floating-point numbers
+    for values in [
+        &b"\x01inf\x001"[..],
+        b"\x0112\x00",
+        b"\x011e\x001",
+        b"\x01-1\x001",
+    ] {
+        assert!(matches!(
+            decode_chunk(b"sCAL", values),
+            Err(DecodeError::ChunkValue(name)) if &name == b"sCAL"
+        ));
+    }
+    let mut data = b"depth\0".to_vec();
+    data.extend([0, 0, 0, 0, 0, 0, 0xFF, 0xFF, 0, 2]);
+    data.extend(b"m\x001\x00NaN");
+    assert!(matches!(
+        decode_chunk(b"pCAL", &data),
+        Err(DecodeError::ChunkValue(name)) if &name == b"pCAL"
+    ));
+}
+
+#[test]
+fn invalid() {
+    // Wrong number of parameters, or not ASCII floating-point numbers
+    for params in [&["1"][..], &["1", "2", "3"], &["1", "0x10"]] {
+        let metadata = PngMetadata {
+            calibration: Some(calibration(Equation::Linear, params)),
+            ..Default::default()
+        };
+        assert!(matches!(
+            encode(metadata),
+            Err(EncodeError::ChunkValue(name)) if &name == b"pCAL"
+        ));
+    }
+    // Non-positive scale
+    let metadata = PngMetadata {
+        subject_physical: Some(SubjectPhysical {
+            unit: ScaleUnit::Meter,
+            width: 0.0,
+            height: 1.0,
+        }),
+        ..Default::default()
+    };
+    assert!(matches!(
+        encode(metadata),
+        Err(EncodeError::ChunkValue(name)) if &name == b"sCAL"
+    ));
+    // Unknown units and layouts
+    assert!(decode_chunk(b"oFFs", &[0, 0, 0, 0, 0, 0, 0, 0, 2]).is_err());
+    assert!(decode_chunk(b"sTER", &[2]).is_err());
+
+    for name in ["pCAL", "sCAL", "sTER"] {
+        let file =
+            std::fs::read(format!("tests/png/bad/huge_{name}_chunk.png"))
+                .unwrap();
+        assert!(decode(&file).is_err(), "{name}");
+    }
+}
+
+#[test]
+fn gif() {
+    let control = GifGraphicControlExt {
+        disposal_method: 2,
+        user_input: true,
+        delay_time: 150,
+    };
+    assert_eq!(control.delay(), 1500);
+    let netscape = GifApplicationExt {
+        identifier: *b"NETSCAPE",
+        authentication: *b"2.0",
+        data: vec![3, 1, 0, 0],
+    };
+    let mut file = Vec::new();
+    Encoder::new(&mut file)
+        .into_step_enc()
+        .gif_graphic_control(control)
+        .gif_application(netscape.clone())
+        .still(&raster())
+        .unwrap();
+    let metadata = decode(&file).unwrap();
+    assert_eq!(metadata.gif_graphic_controls, [control]);
+    assert_eq!(metadata.gif_applications[0], netscape);
+
+    // Also allowed after the image data
+    let mut chunks: Vec<Chunk> = Decoder::new(file.as_slice())
+        .unwrap()
+        .into_chunks()
+        .map(Result::unwrap)
+        .collect();
+    let end = chunks.len() - 1;
+    let late = GifGraphicControlExt {
+        disposal_method: 1,
+        user_input: false,
+        delay_time: 10,
+    };
+    chunks.insert(end, Chunk::GifGraphicControlExt(late));
+    let mut out = Vec::new();
+    let mut encoder = Encoder::new(&mut out).into_chunk_enc();
+    for mut chunk in chunks {
+        encoder.encode(&mut chunk).unwrap();
+    }
+    let metadata = decode(&out).unwrap();
+    assert_eq!(metadata.gif_graphic_controls, [control, late]);
+    assert_eq!(metadata.gif_applications, [netscape]);
+
+    // Invalid user input flag, and too short application extension
+    assert!(matches!(
+        decode_chunk(b"gIFg", &[0, 2, 0, 0]),
+        Err(DecodeError::ChunkValue(name)) if &name == b"gIFg"
+    ));
+    assert!(matches!(
+        decode_chunk(b"gIFx", b"NETSCAPE"),
+        Err(DecodeError::ChunkLength(name)) if &name == b"gIFx"
+    ));
+}
@@ -0,0 +1,643 @@
+use pix::Raster;
+use png_pong::{
+    chunk::{
+        Background, Chromaticities, Chunk, CodePoints, ColorProfile,
+        ContentLightLevel, Gamma, MasteringDisplay, PaletteHistogram, Physical,
+        RenderingIntent, SRgb, SuggestedColor, SuggestedPalette, Text, Time,
+    },
+    decode::Error as DecodeError,
+    encode::Error as EncodeError,
+    Decoder, Encoder, PngMetadata, PngRaster, TextEntry,
+};
+
+fn metadata(filename: &str) -> PngMetadata {
+    let file = std::fs::read(filename).unwrap();
+    let mut steps =
+        Decoder::new(file.as_slice()).expect("Not PNG").into_steps();
+    for step in steps.by_ref() {
+        step.unwrap();
+    }
+    steps.metadata().clone()
+}
+
+fn decode(file: &[u8]) -> (PngRaster, PngMetadata) {
+    let mut steps = Decoder::new(file).expect("Not PNG").into_steps();
+    let raster = steps.next().unwrap().unwrap().raster;
+    assert!(steps.next().is_none());
+    (raster, steps.metadata().clone())
+}
+
+fn encode(
+    raster: &PngRaster,
+    metadata: PngMetadata,
+) -> Result<Vec<u8>, EncodeError> {
+    let mut file = Vec::new();
+    Encoder::new(&mut file)
+        .auto_bit_depth()
+        .into_step_enc()
+        .metadata(metadata)
+        .still(raster)?;
+    Ok(file)
+}
+
+fn chunk_names(file: &[u8]) -> Vec<&'static str> {
+    Decoder::new(file)
+        .unwrap()
+        .into_chunks()
+        .map(|chunk| match chunk.unwrap() {
+            Chunk::ImageHeader(_) => "IHDR",
+            Chunk::Background(_) => "bKGD",
+            Chunk::Physical(_) => "pHYs",
+            Chunk::Time(_) => "tIME",
+            Chunk::Text(_) => "tEXt",
+            Chunk::CompressedText(_) => "zTXt",
+            Chunk::InternationalText(_) => "iTXt",
+            Chunk::Gamma(_) => "gAMA",
+            Chunk::Chromaticities(_) => "cHRM",
+            Chunk::SRgb(_) => "sRGB",
+            Chunk::ColorProfile(_) => "iCCP",
+            Chunk::CodePoints(_) => "cICP",
+            Chunk::MasteringDisplay(_) => "mDCv",
+            Chunk::ContentLightLevel(_) => "cLLi",
+            Chunk::SuggestedPalette(_) => "sPLT",
+            Chunk::Palette(_) => "PLTE",
+            Chunk::PaletteHistogram(_) => "hIST",
+            Chunk::ImageData(_) => "IDAT",
+            Chunk::ImageEnd(_) => "IEND",
+            _ => "other",
+        })
+        .collect()
+}
+
+#[test]
+fn text() {
+    let text = metadata("tests/pngsuite-ancillary/ctzn0g04.png").text;
+    let keys: Vec<&str> = text.iter().map(|entry| entry.key.as_str()).collect();
+    assert_eq!(
+        keys,
+        [
+            "Title",
+            "Author",
+            "Copyright",
+            "Description",
+            "Software",
+            "Disclaimer"
+        ]
+    );
+    assert_eq!(
+        text[0],
+        TextEntry {
+            key: "Title".to_string(),
+            text: "PngSuite".to_string(),
+            langtag: None,
+            transkey: None,
+            compressed: false,
+        }
+    );
+    assert_eq!(
+        text[2],
+        TextEntry {
+            key: "Copyright".to_string(),
+            text: "Copyright Willem van Schaik, Singapore 1995-96".to_string(),
+            langtag: None,
+            transkey: None,
+            compressed: true,
+        }
+    );
+
+    let text = metadata("tests/pngsuite-ancillary/ctjn0g04.png").text;
+    assert_eq!(text.len(), 6);
+    assert_eq!(
+        text[0],
+        TextEntry {
+            key: "Title".to_string(),
+            text: "PngSuite".to_string(),
+            langtag: Some("ja".to_string()),
+            transkey: Some("タイトル".to_string()),
+            compressed: false,
+        }
+    );
+}
+
+#[test]
+fn time() {
+    let time = metadata("tests/pngsuite-ancillary/cm9n0g04.png").time;
+    assert_eq!(
+        time,
+        Some(Time {
+            year: 1999,
+            month: 12,
+            day: 31,
+            hour: 23,
+            minute: 59,
+            second: 59,
+        })
+    );
+    assert_eq!(metadata("tests/pngsuite-ancillary/ct0n0g04.png").time, None);
+}
+
+#[test]
+fn physical() {
+    let physical = metadata("tests/pngsuite-ancillary/cdun2c08.png").physical;
+    assert_eq!(
+        physical,
+        Some(Physical {
+            ppu_x: 1000,
+            ppu_y: 1000,
+            is_meter: true,
+        })
+    );
+}
+
+#[test]
+fn background() {
+    let background =
+        metadata("tests/pngsuite-background/bgbn4a08.png").background;
+    assert_eq!(background, Some(Background::Gray(0)));
+    assert_eq!(
+        metadata("tests/pngsuite-background/bgai4a08.png").background,
+        None
+    );
+}
+
+#[test]
+fn encode_roundtrip() {
+    let file = std::fs::read("tests/pngsuite-ancillary/ctzn0g04.png").unwrap();
+    let (raster, mut metadata) = decode(&file);
+    metadata.text.push(TextEntry {
+        key: "Comment".to_string(),
+        text: "Kommentar".to_string(),
+        langtag: Some("de".to_string()),
+        transkey: Some("Kommentar".to_string()),
+        compressed: true,
+    });
+    metadata.time = Some(Time {
+        year: 2020,
+        month: 2,
+        day: 29,
+        hour: 12,
+        minute: 30,
+        second: 0,
+    });
+    metadata.physical = Some(Physical {
+        ppu_x: 3780,
+        ppu_y: 3780,
+        is_meter: true,
+    });
+    metadata.background = Some(Background::Gray(5 * 17));
+
+    let file = encode(&raster, metadata.clone()).unwrap();
+    assert_eq!(
+        chunk_names(&file),
+        [
+            "IHDR", "gAMA", "bKGD", "pHYs", "tIME", "tEXt", "tEXt", "zTXt",
+            "zTXt", "zTXt", "zTXt", "iTXt", "IDAT", "IEND"
+        ]
+    );
+    let Some(Ok(Chunk::Background(Background::Gray(5)))) =
+        Decoder::new(file.as_slice()).unwrap().into_chunks().nth(2)
+    else {
+        panic!("bKGD should be 5 at 4 bits");
+    };
+
+    let (decoded, decoded_metadata) = decode(&file);
+    assert_eq!(decoded_metadata.text, metadata.text);
+    assert_eq!(decoded_metadata.time, metadata.time);
+    assert_eq!(decoded_metadata.physical, metadata.physical);
+    assert_eq!(decoded_metadata.background, metadata.background);
+    let (PngRaster::Gray8(raster), PngRaster::Gray8(decoded)) =
+        (raster, decoded)
+    else {
+        panic!("Should be Gray8");
+    };
+    assert_eq!(raster.as_u8_slice(), decoded.as_u8_slice());
+}
+
+#[test]
+fn encode_invalid() {
+    let file = std::fs::read("tests/pngsuite-ancillary/ctzn0g04.png").unwrap();
+    let (raster, _) = decode(&file);
+
+    for key in ["", &"k".repeat(80)] {
+        let metadata = PngMetadata {
+            text: vec![TextEntry {
+                key: key.to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        assert!(matches!(
+            encode(&raster, metadata),
+            Err(EncodeError::KeySize(len)) if len == key.len()
+        ));
+    }
+
+    for background in [Background::Palette(0), Background::Rgb(0, 0, 0)] {
+        let metadata = PngMetadata {
+            background: Some(background),
+            ..Default::default()
+        };
+        assert!(matches!(
+            encode(&raster, metadata),
+            Err(EncodeError::Background)
+        ));
+    }
+}
+
+#[test]
+fn latin1() {
+    let text = metadata("tests/png/text/latin1.png").text;
+    assert_eq!(text.len(), 2);
+    assert_eq!(
+        (text[0].key.as_str(), text[0].text.as_str()),
+        ("Author", "René Müller")
+    );
+    assert_eq!(
+        (text[1].key.as_str(), text[1].text.as_str()),
+        ("Grüße", "Grüße aus Köln")
+    );
+
+    // Re-encoded as Latin-1
+    let file = std::fs::read("tests/png/text/latin1.png").unwrap();
+    let (raster, metadata) = decode(&file);
+    let file = encode(&raster, metadata.clone()).unwrap();
+    assert!(file.windows(11).any(|w| w == b"Ren\xe9 M\xfcller"));
+    assert_eq!(chunk_names(&file)[1..3], ["tEXt", "zTXt"]);
+    assert_eq!(decode(&file).1.text, metadata.text);
+}
+
+#[test]
+fn latin1_promotion() {
+    let file = std::fs::read("tests/png/text/latin1.png").unwrap();
+    let (raster, _) = decode(&file);
+    let entry = TextEntry {
+        key: "Title".to_string(),
+        text: "Ελληνικά".to_string(),
+        ..Default::default()
+    };
+    let metadata = PngMetadata {
+        text: vec![entry.clone()],
+        ..Default::default()
+    };
+    let file = encode(&raster, metadata).unwrap();
+    assert_eq!(chunk_names(&file)[1], "iTXt");
+    let text = decode(&file).1.text;
+    assert_eq!(text[0].text, entry.text);
+    assert_eq!(text[0].langtag.as_deref(), Some(""));
+
+    // Keywords can't be promoted
+    let metadata = PngMetadata {
+        text: vec![TextEntry {
+            key: "Τίτλος".to_string(),
+            ..entry
+        }],
+        ..Default::default()
+    };
+    assert!(matches!(
+        encode(&raster, metadata),
+        Err(EncodeError::Latin1)
+    ));
+}
+
+#[test]
+fn latin1_chunk() {
+    let mut file = Vec::new();
+    let mut encoder = Encoder::new(&mut file).into_chunk_enc();
+    let result = encoder.encode(&mut Chunk::Text(Text {
+        key: "Title".to_string(),
+        val: "Ελληνικά".to_string(),
+    }));
+    assert!(matches!(result, Err(EncodeError::Latin1)));
+}
+
+#[test]
+fn color_space() {
+    let ccwn = metadata("tests/pngsuite-ancillary/ccwn2c08.png");
+    assert_eq!(ccwn.gamma, Some(Gamma { gamma: 1.0 }));
+    assert_eq!(
+        ccwn.chromaticities,
+        Some(Chromaticities {
+            white: (0.3127, 0.329),
+            red: (0.64, 0.33),
+            green: (0.3, 0.6),
+            blue: (0.15, 0.06),
+        })
+    );
+    assert_eq!(ccwn.srgb, None);
+    assert_eq!(ccwn.color_profile, None);
+
+    let profile = metadata("tests/png/0.png").color_profile.unwrap();
+    assert_eq!(profile.name, "ICC profile");
+    assert_eq!(profile.profile.len(), 672);
+    assert_eq!(&profile.profile[36..40], b"acsp");
+}
+
+#[test]
+fn encode_color_space() {
+    let file = std::fs::read("tests/pngsuite-ancillary/ccwn2c08.png").unwrap();
+    let (raster, mut metadata) = decode(&file);
+    metadata.gamma = Some(Gamma { gamma: 1.0 / 2.2 });
+    metadata.srgb = Some(SRgb {
+        intent: RenderingIntent::Perceptual,
+    });
+    metadata.color_profile = Some(ColorProfile {
+        name: "Profil für sRGB".to_string(),
+        profile: (0..=255).collect(),
+    });
+
+    let file = encode(&raster, metadata.clone()).unwrap();
+    assert_eq!(
+        chunk_names(&file),
+        ["IHDR", "gAMA", "cHRM", "sRGB", "iCCP", "IDAT", "IEND"]
+    );
+    let decoded = decode(&file).1;
+    assert_eq!(decoded.gamma, Some(Gamma { gamma: 0.45455 }));
+    assert_eq!(decoded.chromaticities, metadata.chromaticities);
+    assert_eq!(decoded.srgb, metadata.srgb);
+    assert_eq!(decoded.color_profile, metadata.color_profile);
+
+    for (gamma, chromaticities) in [
+        (0.0, (0.3127, 0.329)),
+        (1.0, (-0.1, 0.329)),
+        (1.0, (f64::NAN, 0.329)),
+    ] {
+        let metadata = PngMetadata {
+            gamma: Some(Gamma { gamma }),
+            chromaticities: Some(Chromaticities {
+                white: chromaticities,
+                ..metadata.chromaticities.unwrap()
+            }),
+            ..Default::default()
+        };
+        assert!(matches!(
+            encode(&raster, metadata),
+            Err(EncodeError::ChunkValue(_))
+        ));
+    }
+}
+
+#[test]
+fn hdr() {
+    let file = std::fs::read("tests/pngsuite-basic/basn2c16.png").unwrap();
+    let (raster, mut metadata) = decode(&file);
+    // BT.2100 PQ, mastered on a P3 D65 display
+    metadata.code_points = Some(CodePoints {
+        primaries: 9,
+        transfer: 16,
+        matrix: 0,
+        full_range: true,
+    });
+    metadata.mastering_display = Some(MasteringDisplay {
+        red: (0.68, 0.32),
+        green: (0.265, 0.69),
+        blue: (0.15, 0.06),
+        white: (0.3127, 0.329),
+        max_luminance: 1000.0,
+        min_luminance: 0.0001,
+    });
+    metadata.light_level = Some(ContentLightLevel {
+        max_content: 1000.0,
+        max_frame_average: 400.5,
+    });
+
+    let file = encode(&raster, metadata.clone()).unwrap();
+    assert_eq!(
+        chunk_names(&file),
+        ["IHDR", "gAMA", "cICP", "mDCv", "cLLi", "IDAT", "IEND"]
+    );
+    let decoded = decode(&file).1;
+    assert_eq!(decoded.code_points, metadata.code_points);
+    assert_eq!(decoded.mastering_display, metadata.mastering_display);
+    assert_eq!(decoded.light_level, metadata.light_level);
+
+    // Matrix coefficients must be 0 (RGB)
+    let mut invalid = metadata.clone();
+    invalid.code_points.as_mut().unwrap().matrix = 1;
+    assert!(matches!(
+        encode(&raster, invalid),
+        Err(EncodeError::ChunkValue(name)) if &name == b"cICP"
+    ));
+    let mut invalid = metadata;
+    invalid.light_level.as_mut().unwrap().max_content = -1.0;
+    assert!(matches!(
+        encode(&raster, invalid),
+        Err(EncodeError::ChunkValue(name)) if &name == b"cLLi"
+    ));
+}
+
+#[test]
+fn hdr_order() {
+    let file = std::fs::read("tests/pngsuite-basic/basn3p08.png").unwrap();
+    let chunks = |file: &[u8]| -> Vec<Chunk> {
+        Decoder::new(file)
+            .unwrap()
+            .into_chunks()
+            .map(Result::unwrap)
+            .collect()
+    };
+    let code_points = || {
+        Chunk::CodePoints(CodePoints {
+            primaries: 1,
+            transfer: 13,
+            matrix: 0,
+            full_range: true,
+        })
+    };
+    let light_level = || {
+        Chunk::ContentLightLevel(ContentLightLevel {
+            max_content: 80.0,
+            max_frame_average: 80.0,
+        })
+    };
+    // Insert a chunk before the chunk at `index` (counting from the end if
+    // negative), and decode the result
+    let decode = |chunk: Chunk, index: isize| {
+        let mut chunks = chunks(&file);
+        let index = index.rem_euclid(chunks.len() as isize + 1) as usize;
+        chunks.insert(index, chunk);
+        let mut out = Vec::new();
+        let mut encoder = Encoder::new(&mut out).into_chunk_enc();
+        for mut chunk in chunks {
+            encoder.encode(&mut chunk).unwrap();
+        }
+        let mut steps = Decoder::new(out.as_slice()).unwrap().into_steps();
+        for step in steps.by_ref() {
+            step?;
+        }
+        Ok::<_, DecodeError>(steps.metadata().clone())
+    };
+
+    // Before PLTE
+    let metadata = decode(code_points(), 1).unwrap();
+    assert!(metadata.code_points.is_some());
+    let metadata = decode(light_level(), 1).unwrap();
+    assert!(metadata.light_level.is_some());
+    // After PLTE, before IDAT
+    let names = chunk_names(&file);
+    let idat = names.iter().position(|name| *name == "IDAT").unwrap() as isize;
+    assert!(matches!(
+        decode(code_points(), idat),
+        Err(DecodeError::ChunkOrder)
+    ));
+    let metadata = decode(light_level(), idat).unwrap();
+    assert!(metadata.light_level.is_some());
+    // After IDAT
+    assert!(matches!(
+        decode(light_level(), -2),
+        Err(DecodeError::ChunkOrder)
+    ));
+}
+
+#[test]
+fn suggested_palette() {
+    for (filename, sample_depth, white) in [
+        ("tests/pngsuite-palette/ps1n0g08.png", 8, 0xFF),
+        ("tests/pngsuite-palette/ps2n2c16.png", 16, 0xFF),
+    ] {
+        let palettes = metadata(filename).suggested_palettes;
+        assert_eq!(palettes.len(), 1);
+        let palette = &palettes[0];
+        assert_eq!(palette.name, "six-cube");
+        assert_eq!(palette.sample_depth, sample_depth);
+        assert_eq!(palette.entries.len(), 216);
+        assert_eq!(
+            palette.entries[215],
+            SuggestedColor {
+                red: white,
+                green: white,
+                blue: white,
+                alpha: 0xFF,
+                frequency: 0,
+            }
+        );
+    }
+
+    // Roundtrip
+    let file = std::fs::read("tests/pngsuite-palette/ps2n2c16.png").unwrap();
+    let (raster, metadata) = decode(&file);
+    let out = encode(&raster, metadata.clone()).unwrap();
+    assert_eq!(
+        decode(&out).1.suggested_palettes,
+        metadata.suggested_palettes
+    );
+
+    // Names must be unique
+    let mut duplicate = metadata.clone();
+    duplicate
+        .suggested_palettes
+        .push(metadata.suggested_palettes[0].clone());
+    assert!(matches!(
+        encode(&raster, duplicate),
+        Err(EncodeError::ChunkValue(name)) if &name == b"sPLT"
+    ));
+    // Samples must fit in the sample depth
+    let mut wide = metadata;
+    wide.suggested_palettes[0].sample_depth = 8;
+    wide.suggested_palettes[0].entries[0].red = 0x100;
+    assert!(matches!(
+        encode(&raster, wide),
+        Err(EncodeError::ChunkValue(name)) if &name == b"sPLT"
+    ));
+}
+
+#[test]
+fn histogram() {
+    let file = std::fs::read("tests/pngsuite-ancillary/ch1n3p04.png").unwrap();
+    let (raster, metadata) = decode(&file);
+    let histogram = metadata.histogram.clone().unwrap();
+    let PngRaster::Palette(_, ref palette, _) = raster else {
+        panic!("Should be a palette");
+    };
+    assert_eq!(histogram.frequencies.len(), palette.len());
+    assert_eq!(histogram.frequencies[..4], [64, 112, 48, 96]);
+
+    // Roundtrip, after PLTE
+    let out = encode(&raster, metadata.clone()).unwrap();
+    let names = chunk_names(&out);
+    let position = |chunk| names.iter().position(|name| *name == chunk);
+    assert!(position("PLTE") < position("hIST"));
+    assert_eq!(decode(&out).1.histogram, Some(histogram.clone()));
+
+    // Must match the palette
+    let mut short = metadata.clone();
+    short.histogram.as_mut().unwrap().frequencies.pop();
+    assert!(matches!(
+        encode(&raster, short),
+        Err(EncodeError::ChunkValue(name)) if &name == b"hIST"
+    ));
+    let file = std::fs::read("tests/pngsuite-basic/basn2c08.png").unwrap();
+    let (rgb, _) = decode(&file);
+    let metadata = PngMetadata {
+        histogram: Some(histogram.clone()),
+        ..Default::default()
+    };
+    assert!(matches!(
+        encode(&rgb, metadata),
+        Err(EncodeError::ChunkValue(name)) if &name == b"hIST"
+    ));
+
+    // Must come after PLTE (which RGB images don't have here)
+    let mut chunks: Vec<Chunk> = Decoder::new(file.as_slice())
+        .unwrap()
+        .into_chunks()
+        .map(Result::unwrap)
+        .collect();
+    chunks.insert(1, Chunk::PaletteHistogram(histogram));
+    let mut out = Vec::new();
+    let mut encoder = Encoder::new(&mut out).into_chunk_enc();
+    for mut chunk in chunks {
+        encoder.encode(&mut chunk).unwrap();
+    }
+    assert!(matches!(
+        Decoder::new(out.as_slice()).unwrap().into_steps().next(),
+        Some(Err(DecodeError::ChunkOrder))
+    ));
+}
+
+#[test]
+fn suggest_palette() {
+    let encode = |raster: &PngRaster, max_colors| {
+        let mut file = Vec::new();
+        Encoder::new(&mut file)
+            .into_step_enc()
+            .suggest_palette(max_colors)
+            .still(raster)
+            .unwrap();
+        decode(&file).1
+    };
+
+    // Most frequent colors first
+    let raster = PngRaster::Rgb8(Raster::with_u8_buffer(
+        4,
+        1,
+        [0, 0, 255, 255, 0, 0, 0, 0, 255, 0, 0, 255],
+    ));
+    let color = |red, blue, frequency| SuggestedColor {
+        red,
+        green: 0,
+        blue,
+        alpha: 255,
+        frequency,
+    };
+    let palettes = encode(&raster, 256).suggested_palettes;
+    assert_eq!(
+        palettes,
+        [SuggestedPalette {
+            name: "2 colors".to_string(),
+            sample_depth: 8,
+            entries: vec![color(0, 255, 65535), color(255, 0, 21845)],
+        }]
+    );
+    let palettes = encode(&raster, 1).suggested_palettes;
+    assert_eq!(palettes[0].entries, [color(0, 255, 65535)]);
+
+    // Histogram for palette rasters
+    let file = std::fs::read("tests/pngsuite-ancillary/ch2n3p08.png").unwrap();
+    let (raster, _) = decode(&file);
+    let metadata = encode(&raster, 256);
+    assert!(metadata.suggested_palettes.is_empty());
+    let PaletteHistogram { frequencies } = metadata.histogram.unwrap();
+    assert_eq!(frequencies.len(), 256);
+    assert_eq!(frequencies.iter().max(), Some(&65535));
+    assert!(!frequencies.contains(&0));
+}
@@ -0,0 +1,190 @@
+use pix::Raster;
+use png_pong::{
+    chunk::Transparency, encode::Error as EncodeError, Decoder, Encoder,
+    PngRaster,
+};
+
+const WIDTH: u32 = 13;
+const HEIGHT: u32 = 9;
+
+fn decode(filename: &str, keep_key: bool) -> (PngRaster, Option<Transparency>) {
+    decode_file(&std::fs::read(filename).unwrap(), keep_key)
+}
+
+fn decode_file(
+    file: &[u8],
+    keep_key: bool,
+) -> (PngRaster, Option<Transparency>) {
+    let mut decoder = Decoder::new(file).expect("Not PNG");
+    if keep_key {
+        decoder = decoder.keep_transparency_key();
+    }
+    let mut steps = decoder.into_steps();
+    let raster = steps.next().unwrap().unwrap().raster;
+    (raster, steps.transparency().cloned())
+}
+
+fn encode(
+    raster: &PngRaster,
+    key: Transparency,
+    bit_depth: Option<u8>,
+) -> Result<Vec<u8>, EncodeError> {
+    let mut file = Vec::new();
+    let mut encoder = Encoder::new(&mut file);
+    if bit_depth.is_none() {
+        encoder = encoder.auto_bit_depth();
+    }
+    encoder
+        .into_step_enc()
+        .transparency_key(key)
+        .still(raster)?;
+    Ok(file)
+}
+
+#[test]
+fn gray_key() {
+    for (bit_depth, scale) in [(4, 17), (8, 1)] {
+        let filename = format!("tests/png/trns/g{bit_depth:02}.png");
+        let (PngRaster::Graya8(raster), Some(Transparency::GrayKey(3))) =
+            decode(&filename, false)
+        else {
+            panic!("{filename} should be Graya8");
+        };
+        for y in 0..HEIGHT {
+            for x in 0..WIDTH {
+                let value = ((x + 2 * y) % (1 << bit_depth)) as u8;
+                let alpha = if value == 3 { 0 } else { 255 };
+                let value = value * scale;
+                let i = (y * WIDTH + x) as usize * 2;
+                assert_eq!(raster.as_u8_slice()[i..][..2], [value, alpha]);
+            }
+        }
+    }
+
+    let (PngRaster::Graya16(raster), _) =
+        decode("tests/png/trns/g16.png", false)
+    else {
+        panic!("g16 should be Graya16");
+    };
+    let transparent_count = raster
+        .as_u8_slice()
+        .chunks(4)
+        .filter(|p| p[2..] == [0, 0])
+        .count();
+    assert_eq!(transparent_count, 2);
+    assert_eq!(raster.as_u8_slice()[3 * 4..][..4], [3, 3, 0, 0]);
+}
+
+#[test]
+fn rgb_key() {
+    let (PngRaster::Rgba8(raster), _) = decode("tests/png/trns/c08.png", false)
+    else {
+        panic!("c08 should be Rgba8");
+    };
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            let alpha = if (x, y) == (3, 2) { 0 } else { 255 };
+            let i = (y * WIDTH + x) as usize * 4;
+            assert_eq!(raster.as_u8_slice()[i + 3], alpha);
+        }
+    }
+
+    let (PngRaster::Rgba16(raster), _) =
+        decode("tests/png/trns/c16.png", false)
+    else {
+        panic!("c16 should be Rgba16");
+    };
+    let i = (2 * WIDTH + 3) as usize * 8;
+    assert_eq!(raster.as_u8_slice()[i..][..8], [48, 48, 32, 32, 1, 1, 0, 0]);
+    let transparent_count = raster
+        .as_u8_slice()
+        .chunks(8)
+        .filter(|p| p[6..] == [0, 0])
+        .count();
+    assert_eq!(transparent_count, 1);
+}
+
+#[test]
+fn keep_key() {
+    let (raster, key) = decode("tests/png/trns/g04.png", true);
+    assert!(matches!(raster, PngRaster::Gray8(_)));
+    assert_eq!(key, Some(Transparency::GrayKey(3)));
+
+    let (raster, key) = decode("tests/png/trns/c16.png", true);
+    assert!(matches!(raster, PngRaster::Rgb16(_)));
+    assert_eq!(key, Some(Transparency::RgbKey(12336, 8224, 257)));
+}
+
+#[test]
+fn encode_gray_key() {
+    let samples: Vec<u8> = (0..HEIGHT)
+        .flat_map(|y| (0..WIDTH).map(move |x| ((x + 2 * y) % 16) as u8 * 17))
+        .collect();
+    let raster = PngRaster::Gray8(Raster::with_u8_buffer(
+        WIDTH,
+        HEIGHT,
+        samples.clone(),
+    ));
+    for (bit_depth, key) in [(Some(8), 51), (None, 3)] {
+        let file =
+            encode(&raster, Transparency::GrayKey(51), bit_depth).unwrap();
+        let (_, decoded) = decode_file(&file, true);
+        assert_eq!(decoded, Some(Transparency::GrayKey(key)));
+        let (PngRaster::Graya8(decoded), _) = decode_file(&file, false) else {
+            panic!("Should decode as Graya8");
+        };
+        for (pixel, value) in decoded.as_u8_slice().chunks(2).zip(&samples) {
+            let alpha = if *value == 51 { 0 } else { 255 };
+            assert_eq!(pixel, [*value, alpha]);
+        }
+    }
+
+    // Key doesn't fit in the lowered bit depth, so it's kept at 8 bits.
+    let file = encode(&raster, Transparency::GrayKey(50), None).unwrap();
+    let (_, decoded) = decode_file(&file, true);
+    assert_eq!(decoded, Some(Transparency::GrayKey(50)));
+}
+
+#[test]
+fn encode_rgb_key() {
+    let samples: Vec<u8> = (0..HEIGHT)
+        .flat_map(|y| {
+            (0..WIDTH).flat_map(move |x| [x as u8 * 16, y as u8 * 16, 0])
+        })
+        .collect();
+    let raster =
+        PngRaster::Rgb8(Raster::with_u8_buffer(WIDTH, HEIGHT, samples));
+    let file =
+        encode(&raster, Transparency::RgbKey(48, 32, 0), Some(8)).unwrap();
+    let (PngRaster::Rgba8(decoded), _) = decode_file(&file, false) else {
+        panic!("Should decode as Rgba8");
+    };
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            let alpha = if (x, y) == (3, 2) { 0 } else { 255 };
+            let i = (y * WIDTH + x) as usize * 4;
+            assert_eq!(decoded.as_u8_slice()[i + 3], alpha);
+        }
+    }
+}
+
+#[test]
+fn encode_invalid_key() {
+    let gray = PngRaster::Gray8(Raster::with_clear(WIDTH, HEIGHT));
+    let rgb = PngRaster::Rgb8(Raster::with_clear(WIDTH, HEIGHT));
+    for (raster, key) in [
+        (&gray, Transparency::RgbKey(0, 0, 0)),
+        (&rgb, Transparency::GrayKey(0)),
+        (&rgb, Transparency::RgbKey(0, 256, 0)),
+        (&gray, Transparency::Palette(vec![0])),
+    ] {
+        assert!(matches!(
+            encode(raster, key, Some(8)),
+            Err(EncodeError::TransparencyKey)
+        ));
+    }
+    assert!(matches!(
+        encode(&gray, Transparency::GrayKey(256), Some(8)),
+        Err(EncodeError::BitDepth(8))
+    ));
+}
@@ -0,0 +1,171 @@
+use pix::Raster;
+use png_pong::{
+    chunk::{Chunk, ChunkPosition, ImageEnd, Unknown},
+    decode::Error as DecodeError,
+    Decoder, Encoder, PngRaster,
+};
+
+/// Encode a 1x1 image with a chunk inserted before the chunk at `index`
+/// (counting from the end if negative).
+fn with_chunk(chunk: Chunk, index: isize) -> Vec<u8> {
+    let mut file = Vec::new();
+    Encoder::new(&mut file)
+        .into_step_enc()
+        .still(&PngRaster::Gray8(Raster::with_u8_buffer(1, 1, [0])))
+        .unwrap();
+    let mut chunks: Vec<Chunk> = Decoder::new(file.as_slice())
+        .unwrap()
+        .into_chunks()
+        .map(Result::unwrap)
+        .collect();
+    let index = index.rem_euclid(chunks.len() as isize + 1) as usize;
+    chunks.insert(index, chunk);
+    let mut out = Vec::new();
+    let mut encoder = Encoder::new(&mut out).into_chunk_enc();
+    for mut chunk in chunks {
+        encoder.encode(&mut chunk).unwrap();
+    }
+    out
+}
+
+fn unknown(name: &[u8; 4]) -> Chunk {
+    Chunk::Unknown(Unknown {
+        name: *name,
+        data: vec![1, 2, 3],
+    })
+}
+
+fn decode(file: &[u8]) -> Result<(), DecodeError> {
+    for step in Decoder::new(file).unwrap().into_steps() {
+        step?;
+    }
+    Ok(())
+}
+
+#[test]
+fn property_bits() {
+    let chunk = |name: &[u8; 4]| Unknown {
+        name: *name,
+        data: Vec::new(),
+    };
+    assert!(chunk(b"IDAT").is_critical());
+    assert!(chunk(b"IDAT").is_public());
+    assert!(!chunk(b"IDAT").is_safe_to_copy());
+    assert!(!chunk(b"juNk").is_critical());
+    assert!(!chunk(b"juNk").is_public());
+    assert!(chunk(b"oFFs").is_public());
+    assert!(chunk(b"juNk").is_safe_to_copy());
+    assert!(!chunk(b"prVW").is_public());
+    assert!(!chunk(b"prVW").is_safe_to_copy());
+}
+
+#[test]
+fn unknown_chunks() {
+    // Ancillary chunks are accepted before and after the image data
+    for index in [1, -2] {
+        for name in [b"juNk", b"juNK", b"prVw"] {
+            decode(&with_chunk(unknown(name), index)).unwrap();
+        }
+    }
+    // Critical chunks can't be skipped
+    for index in [1, -2] {
+        assert!(matches!(
+            decode(&with_chunk(unknown(b"JUNK"), index)),
+            Err(DecodeError::UnknownChunkType(name)) if &name == b"JUNK"
+        ));
+    }
+}
+
+/// Get the chunk names of a file.
+fn names(file: &[u8]) -> Vec<[u8; 4]> {
+    let mut names = Vec::new();
+    let mut at = 8;
+    while at < file.len() {
+        let len = u32::from_be_bytes(file[at..at + 4].try_into().unwrap());
+        names.push(file[at + 4..at + 8].try_into().unwrap());
+        at += 12 + len as usize;
+    }
+    names
+}
+
+#[test]
+fn roundtrip() {
+    // Palette image with unknown chunks in each region
+    let file = std::fs::read("tests/png/depth/p1n.png").unwrap();
+    let mut chunks: Vec<Chunk> = Decoder::new(file.as_slice())
+        .unwrap()
+        .into_chunks()
+        .map(Result::unwrap)
+        .filter(|chunk| {
+            use Chunk::*;
+            matches!(chunk, ImageHeader(_) | Palette(_) | ImageData(_))
+        })
+        .collect();
+    chunks.push(Chunk::ImageEnd(ImageEnd));
+    chunks.insert(chunks.len() - 1, unknown(b"afTr"));
+    let idat = chunks
+        .iter()
+        .position(|chunk| matches!(chunk, Chunk::ImageData(_)))
+        .unwrap();
+    chunks.insert(idat, unknown(b"miDD"));
+    chunks.insert(idat, unknown(b"miDd"));
+    chunks.insert(1, unknown(b"beFr"));
+    let mut file = Vec::new();
+    let mut encoder = Encoder::new(&mut file).into_chunk_enc();
+    for mut chunk in chunks {
+        encoder.encode(&mut chunk).unwrap();
+    }
+
+    let mut steps = Decoder::new(file.as_slice()).unwrap().into_steps();
+    let raster = steps.next().unwrap().unwrap().raster;
+    assert!(steps.next().is_none());
+    let metadata = steps.metadata().clone();
+    let unknowns: Vec<_> = metadata
+        .unknown_chunks
+        .iter()
+        .map(|(position, chunk)| (*position, chunk.name))
+        .collect();
+    assert_eq!(
+        unknowns,
+        [
+            (ChunkPosition::BeforePalette, *b"beFr"),
+            (ChunkPosition::BeforeImageData, *b"miDd"),
+            (ChunkPosition::BeforeImageData, *b"miDD"),
+            (ChunkPosition::AfterImageData, *b"afTr"),
+        ]
+    );
+
+    // Unsafe to copy chunks are dropped unless requested
+    for copy_unsafe in [false, true] {
+        let mut out = Vec::new();
+        let mut encoder = Encoder::new(&mut out)
+            .into_step_enc()
+            .metadata(metadata.clone());
+        if copy_unsafe {
+            encoder = encoder.copy_unsafe_chunks();
+        }
+        encoder.still(&raster).unwrap();
+        let mut expected = vec![*b"IHDR", *b"beFr", *b"PLTE", *b"miDd"];
+        if copy_unsafe {
+            expected.push(*b"miDD");
+        }
+        expected.extend([*b"IDAT", *b"afTr", *b"IEND"]);
+        assert_eq!(names(&out), expected);
+    }
+
+    // Critical chunks are never written
+    let mut out = Vec::new();
+    Encoder::new(&mut out)
+        .into_step_enc()
+        .unknown_chunk(
+            ChunkPosition::AfterImageData,
+            Unknown {
+                name: *b"JUNK",
+                data: Vec::new(),
+            },
+        )
+        .copy_unsafe_chunks()
+        .still(&raster)
+        .unwrap();
+    assert_eq!(names(&out), [*b"IHDR", *b"PLTE", *b"IDAT", *b"IEND"]);
+}